@@ -1,20 +1,15 @@
 extern crate gcc;
 
 use std::env;
-use std::error::Error;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::BufReader;
 use std::io::BufRead;
-use std::path::Path;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
-#[derive(PartialEq,Debug)]
-enum Context {
-    None,
-    Asm,
-    Crypto,
-    Ssl,
-    Ignore,
-}
+// the parsers of the generated files, shared with tests/build_sources.rs
+include!("build/sources.rs");
 
 #[allow(non_camel_case_types)]
 #[derive(PartialEq,Debug)]
@@ -29,10 +24,15 @@ enum CryptoTarget {
     WinX86_64,
 }
 
-fn check_env(src: CryptoTarget) -> Context {
+/// Returns the target whose assembly sources are compiled, or None to build
+/// the portable C implementation with OPENSSL_NO_ASM.
+fn crypto_target() -> Option<CryptoTarget> {
+    if env::var_os("CARGO_FEATURE_NO_ASM").is_some() {
+        return None;
+    }
+
     let target = env::var("TARGET").unwrap();
     let t: Vec<&str> = target.split('-').collect();
-    assert!(t.len() >= 3);
 
     // <arch><sub>-<vendor>-<sys>-<abi>, where:
     //  arch = x86, arm, thumb, mips, etc.
@@ -40,140 +40,779 @@ fn check_env(src: CryptoTarget) -> Context {
     //  vendor = pc, apple, nvidia, ibm, etc.
     //  sys = none, linux, win32, darwin, cuda, etc.
     //  abi = eabi, gnu, android, macho, elf, etc.
-    let src_target = match t[0] {
-        "x86" => {
-            match t[2] {
-                "darwin" => CryptoTarget::MacX86,
-                "linux" => CryptoTarget::LinuxX86,
-                "win32" => CryptoTarget::WinX86,
-                _ => panic!("unimplemented target {:?}", target),
-            }
+    // The vendor is omitted by some, e.g. aarch64-linux-android, and targets
+    // such as wasm32-wasip1 have only two components.
+    let sys = t.get(2).cloned().unwrap_or("");
+    let linux = t.get(1) == Some(&"linux") || sys == "linux";
+    let os = match sys {
+        _ if linux => Some("linux"),
+        "darwin" => Some("darwin"),
+        "windows" | "win32" => Some("windows"),
+        _ => None,
+    };
+    let src_target = match (t[0], os) {
+        ("x86", Some("darwin")) |
+        ("i386", Some("darwin")) |
+        ("i686", Some("darwin")) => Some(CryptoTarget::MacX86),
+        ("x86", Some("linux")) |
+        ("i386", Some("linux")) |
+        ("i586", Some("linux")) |
+        ("i686", Some("linux")) => Some(CryptoTarget::LinuxX86),
+        ("x86", Some("windows")) |
+        ("i586", Some("windows")) |
+        ("i686", Some("windows")) => Some(CryptoTarget::WinX86),
+        ("x86_64", Some("darwin")) => Some(CryptoTarget::MacX86_64),
+        ("x86_64", Some("linux")) => Some(CryptoTarget::LinuxX86_64),
+        ("x86_64", Some("windows")) => Some(CryptoTarget::WinX86_64),
+        ("aarch64", _) if linux => Some(CryptoTarget::LinuxAarch64),
+        (arch, _) if (arch.starts_with("arm") || arch.starts_with("thumbv7")) && linux => {
+            Some(CryptoTarget::LinuxArm)
         }
-        "x86_64" => {
-            match t[2] {
-                "darwin" => CryptoTarget::MacX86_64,
-                "linux" => CryptoTarget::LinuxX86_64,
-                "win32" => CryptoTarget::WinX86_64,
-                _ => panic!("unimplemented target {:?}", target),
-            }
+        _ => None,
+    };
+
+    if src_target.is_none() {
+        println!("cargo:warning=no assembly for target {:?}, building without it",
+                 target);
+    }
+    // the NASM sources of Windows don't support BORINGSSL_PREFIX
+    if os == Some("windows") && src_target.is_some() &&
+       env::var_os("CARGO_FEATURE_PREFIX_SYMBOLS").is_some() {
+        println!("cargo:warning=the prefix-symbols feature builds {:?} without assembly",
+                 target);
+        return None;
+    }
+    src_target
+}
+
+impl CryptoTarget {
+    /// Returns the platform name of the target's assembly sources, e.g. the
+    /// linux_x86_64 of crypto_sources_linux_x86_64.
+    fn platform(&self) -> &'static str {
+        match *self {
+            CryptoTarget::LinuxAarch64 => "linux_aarch64",
+            CryptoTarget::LinuxArm => "linux_arm",
+            CryptoTarget::LinuxX86 => "linux_x86",
+            CryptoTarget::LinuxX86_64 => "linux_x86_64",
+            CryptoTarget::MacX86 => "mac_x86",
+            CryptoTarget::MacX86_64 => "mac_x86_64",
+            CryptoTarget::WinX86 => "win_x86",
+            CryptoTarget::WinX86_64 => "win_x86_64",
+        }
+    }
+}
+
+/// The directory generate_build_files.py is run in by roll.sh. The paths in
+/// the files it generates are relative to it.
+const GENERATED_DIR: &str = "third_party/boringssl";
+
+/// Reads the sources to compile for target from the lists written by
+/// generate_build_files.py, in its bazel, json or cmake layout.
+fn read_sources(target: &Option<CryptoTarget>) -> Sources {
+    let dir = Path::new(GENERATED_DIR);
+    let path = match ["BUILD.generated.bzl", "sources.json", "sources.cmake"]
+        .iter()
+        .map(|f| dir.join(f))
+        .find(|p| p.exists()) {
+        Some(path) => path,
+        None => {
+            panic!("no BUILD.generated.bzl, sources.json or sources.cmake in {}, run roll.sh",
+                   GENERATED_DIR)
+        }
+    };
+    let tokens = tokenize(&read_file(&path));
+    let lists = if path.extension() == Some("cmake".as_ref()) {
+        parse_cmake(&tokens)
+    } else {
+        parse_lists(&tokens)
+    };
+
+    let mut sources = select_sources(lists, target.as_ref().map(|t| t.platform()));
+    for name in &sources.unknown {
+        println!("cargo:warning=ignoring unknown source list {:?} in {}",
+                 name,
+                 path.display());
+    }
+    if sources.c.is_empty() {
+        panic!("no sources found in {}", path.display());
+    }
+    for list in &mut [&mut sources.asm, &mut sources.c, &mut sources.cc] {
+        for file in list.iter_mut() {
+            *file = format!("{}/{}", GENERATED_DIR, file);
         }
-        _ => panic!("unimplemented target {:?}", target),
+    }
+    sources
+}
+
+fn read_file(path: &Path) -> String {
+    let mut contents = String::new();
+    match File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+        Err(why) => panic!("couldn't read {}: {}", path.display(), why),
+        Ok(_) => contents,
+    }
+}
+
+/// The BORINGSSL_API_VERSION of the vendored BoringSSL, which provides the
+/// declarations in ffi.rs outside of the quic feature and of the private key
+/// method, whose layout check_private_key_method checks instead.
+const MIN_API_VERSION: u32 = 3;
+
+/// The oldest BORINGSSL_API_VERSION providing the secret-based
+/// SSL_QUIC_METHOD of the quic feature.
+const MIN_QUIC_API_VERSION: u32 = 10;
+
+/// The symbol prefix of the prefix-symbols feature unless BORINGSSL_PREFIX is
+/// set.
+const DEFAULT_PREFIX: &str = "boringssl_rs";
+
+/// Links a prebuilt BoringSSL from BORINGSSL_LIB_DIR instead of compiling the
+/// vendored sources. BORINGSSL_INCLUDE_DIR (default: the include directory
+/// next to the lib directory) is checked for a compatible version, and
+/// BORINGSSL_LINK selects "static" (default) or "dylib" linking.
+fn link_prebuilt(lib_dir: PathBuf) {
+    let include_dir = match env::var_os("BORINGSSL_INCLUDE_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => lib_dir.join("../include"),
     };
+    check_api_version(&include_dir);
+    check_private_key_method(&include_dir);
 
-    if src == src_target {
-        return Context::Asm;
+    let kind = env::var("BORINGSSL_LINK").unwrap_or_else(|_| "static".to_string());
+    if kind != "static" && kind != "dylib" {
+        panic!("BORINGSSL_LINK must be \"static\" or \"dylib\", not {:?}", kind);
+    }
+    println!("cargo:rustc-link-search=native={}", lib_dir.display());
+    println!("cargo:rustc-link-lib={}=ssl", kind);
+    println!("cargo:rustc-link-lib={}=crypto", kind);
+
+    // a static libssl needs the C++ runtime, a shared one links it itself
+    if kind == "static" {
+        if let Some(stdlib) = cpp_stdlib() {
+            println!("cargo:rustc-link-lib={}", stdlib);
+        }
     }
-    Context::Ignore
 }
 
-fn get_filename(s: &mut String) -> String {
-    // remove ," at the end
-    let i = s.len() - 2;
-    s.truncate(i);
-    String::from("third_party/boringssl/") + &s[5..]
+/// Links the system OpenSSL into the tests of the prefix-symbols feature by
+/// the paths of its libs, found in OPENSSL_LIB_DIR or through pkg-config, so
+/// that they can't resolve to BoringSSL's libssl and libcrypto in
+/// BORINGSSL_LIB_DIR. Without OpenSSL, link_openssl isn't set and
+/// tests/prefix.rs is skipped.
+fn link_openssl_for_tests() {
+    let lib_dir = match env::var_os("OPENSSL_LIB_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            let output = Command::new("pkg-config")
+                .arg("--variable=libdir")
+                .arg("openssl")
+                .output();
+            match output {
+                Ok(ref output) if output.status.success() => {
+                    PathBuf::from(String::from_utf8_lossy(&output.stdout).trim())
+                }
+                _ => {
+                    println!("cargo:warning=OpenSSL not found, set OPENSSL_LIB_DIR to test \
+                              linking with it");
+                    return;
+                }
+            }
+        }
+    };
+    let ext = if env::var("TARGET").unwrap().contains("apple") { "dylib" } else { "so" };
+    let libs = [lib_dir.join(format!("libssl.{}", ext)),
+                lib_dir.join(format!("libcrypto.{}", ext))];
+    if libs.iter().any(|lib| !lib.exists()) {
+        println!("cargo:warning=no libssl.{0} and libcrypto.{0} in {1}, OpenSSL isn't tested",
+                 ext,
+                 lib_dir.display());
+        return;
+    }
+    for lib in &libs {
+        println!("cargo:rustc-link-arg-tests={}", lib.display());
+    }
+    println!("cargo:rustc-cfg=link_openssl");
 }
 
-fn main() {
-    // lists of source files
-    let mut asm_src: Vec<String> = Vec::new();
-    let mut crypto_src: Vec<String> = Vec::new();
-    let mut ssl_src: Vec<String> = Vec::new();
-
-    // parse generated bazel file
-    let build_path = Path::new("third_party/boringssl/BUILD.generated.bzl");
-    let build_file = match File::open(&build_path) {
-        // The `description` method of `io::Error` returns a string that
-        // describes the error
-        Err(why) => panic!("couldn't open BUILD file: {}", why.description()),
+/// Panics unless the headers in include_dir are from a BoringSSL at least as
+/// new as MIN_API_VERSION, or MIN_QUIC_API_VERSION with the quic feature.
+fn check_api_version(include_dir: &Path) {
+    let min_version = if env::var_os("CARGO_FEATURE_QUIC").is_some() {
+        MIN_QUIC_API_VERSION
+    } else {
+        MIN_API_VERSION
+    };
+    let base_h = include_dir.join("openssl/base.h");
+    let file = match File::open(&base_h) {
+        Err(why) => panic!("couldn't open {}: {}", base_h.display(), why),
         Ok(file) => file,
     };
+    let mut version = None;
+    for line in BufReader::new(file).lines() {
+        let line = line.unwrap();
+        let mut words = line.split_whitespace();
+        if words.next() == Some("#define") && words.next() == Some("BORINGSSL_API_VERSION") {
+            version = words.next().and_then(|v| v.parse::<u32>().ok());
+            break;
+        }
+    }
+    match version {
+        None => {
+            panic!("{} does not define BORINGSSL_API_VERSION, is it BoringSSL?",
+                   base_h.display())
+        }
+        Some(v) if v < min_version => {
+            panic!("BoringSSL in {} has API version {}, at least {} is required",
+                   include_dir.display(),
+                   v,
+                   min_version)
+        }
+        Some(_) => {}
+    }
+}
 
-    let reader = BufReader::new(build_file);
-    let mut ctx = Context::None;
-    for l in reader.lines() {
-        let mut line = l.unwrap();
+/// Sets boringssl_private_key_method, which enables PrivateKeyMethod, if the
+/// SSL_PRIVATE_KEY_METHOD in the headers in include_dir consists of the sign,
+/// decrypt and complete hooks ffi.rs declares. Older BoringSSL, such as the
+/// vendored one, has more hooks in another layout, and BoringSSL would call
+/// the wrong ones through the struct of ffi.rs.
+fn check_private_key_method(include_dir: &Path) {
+    let ssl_h = include_dir.join("openssl/ssl.h");
+    let header = read_file(&ssl_h);
+    let fields = match header.find("struct ssl_private_key_method_st {") {
+        Some(start) => {
+            let fields = &header[start..];
+            &fields[..fields.find('}').unwrap_or(fields.len())]
+        }
+        None => panic!("{} does not define SSL_PRIVATE_KEY_METHOD", ssl_h.display()),
+    };
+    // the hooks are the function pointer fields, e.g. (*sign)(
+    let hooks: Vec<&str> = fields.split("(*")
+        .skip(1)
+        .filter_map(|field| field.split(")(").next())
+        .collect();
+    if hooks == ["sign", "decrypt", "complete"] {
+        println!("cargo:rustc-cfg=boringssl_private_key_method");
+    }
+}
 
-        // skip empty lines
-        if line.is_empty() {
-            continue;
+fn main() {
+    // listing these disables cargo's default of rerunning on any change, so
+    // the vendored sources are listed as well
+    for var in &["BORINGSSL_LIB_DIR",
+                 "BORINGSSL_INCLUDE_DIR",
+                 "BORINGSSL_LINK",
+                 "BORINGSSL_PREFIX",
+                 "CXXSTDLIB",
+                 "NASM",
+                 "NM",
+                 "OPENSSL_LIB_DIR"] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=build/sources.rs");
+    println!("cargo:rerun-if-changed=third_party/boringssl");
+    println!("cargo:rustc-check-cfg=cfg(link_openssl)");
+    println!("cargo:rustc-check-cfg=cfg(boringssl_sys)");
+    println!("cargo:rustc-check-cfg=cfg(boringssl_private_key_method)");
+    // sys.rs is only rewritten by roll.sh, which also changes the sources
+    if Path::new("src/sys.rs").exists() {
+        println!("cargo:rerun-if-changed=src/sys.rs");
+        // the generated bindings link the unprefixed names
+        if env::var_os("CARGO_FEATURE_PREFIX_SYMBOLS").is_none() {
+            println!("cargo:rustc-cfg=boringssl_sys");
+        }
+    }
+    if env::var_os("CARGO_FEATURE_PREFIX_SYMBOLS").is_some() {
+        link_openssl_for_tests();
+    }
+    if let Some(lib_dir) = env::var_os("BORINGSSL_LIB_DIR") {
+        // a prebuilt BoringSSL must have been built with the same prefix
+        if env::var_os("CARGO_FEATURE_PREFIX_SYMBOLS").is_some() {
+            symbol_prefix();
+        }
+        link_prebuilt(PathBuf::from(lib_dir));
+        if env::var_os("CARGO_FEATURE_BORINGSSL_TESTS").is_some() {
+            println!("cargo:warning=BoringSSL's tests are not built for a prebuilt BoringSSL");
+            write_tests(&[]);
         }
+        return;
+    }
 
-        // skip comments
-        if line.starts_with("#") {
-            continue;
+    check_api_version(&Path::new(GENERATED_DIR).join("src/include"));
+    check_private_key_method(&Path::new(GENERATED_DIR).join("src/include"));
+    let mut target = crypto_target();
+    let sources = read_sources(&target);
+    if target.is_some() && sources.asm.is_empty() {
+        println!("cargo:warning=no assembly sources for {:?}, building without it",
+                 target.unwrap());
+        target = None;
+    }
+
+    if env::var_os("CARGO_FEATURE_PREFIX_SYMBOLS").is_none() {
+        let libs = compile(&target, &sources, None);
+        if env::var_os("CARGO_FEATURE_BORINGSSL_TESTS").is_some() {
+            build_tests(&target, &libs, None);
         }
+        return;
+    }
 
-        if ctx == Context::None {
-            if !line.ends_with(" = [") {
-                panic!("invalid line {:?}", line);
-            }
+    // To prefix the symbols, BoringSSL is compiled once to find the symbols
+    // it defines and then again with a header renaming all of them.
+    let prefix = symbol_prefix();
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let libs = compile(&target, &sources, None);
+    let mut symbols = Vec::new();
+    for lib in &libs {
+        symbols.extend(read_symbols(&out_dir.join(lib)));
+    }
+    symbols.sort();
+    symbols.dedup();
+    let include_dir = out_dir.join("prefix_include");
+    write_prefix_headers(&include_dir, &symbols);
+    let libs = compile(&target, &sources, Some((&prefix, &include_dir)));
+    check_prefixed(&libs, &prefix);
+    if env::var_os("CARGO_FEATURE_BORINGSSL_TESTS").is_some() {
+        build_tests(&target, &libs, Some((&prefix, &include_dir)));
+    }
+}
 
-            // remove " = [" at the end
-            let i = line.len() - 4;
-            line.truncate(i);
-
-            // match context
-            ctx = match line.as_ref() {
-                "crypto_headers" => Context::Ignore,
-                "crypto_internal_headers" => Context::Ignore,
-                "crypto_sources" => Context::Crypto,
-                "crypto_sources_linux_aarch64" => check_env(CryptoTarget::LinuxAarch64),
-                "crypto_sources_linux_arm" => check_env(CryptoTarget::LinuxArm),
-                "crypto_sources_linux_ppc64le" => Context::Ignore,
-                "crypto_sources_linux_x86" => check_env(CryptoTarget::LinuxX86),
-                "crypto_sources_linux_x86_64" => check_env(CryptoTarget::LinuxX86_64),
-                "crypto_sources_mac_x86" => check_env(CryptoTarget::MacX86),
-                "crypto_sources_mac_x86_64" => check_env(CryptoTarget::MacX86_64),
-                "crypto_sources_win_x86" => check_env(CryptoTarget::WinX86),
-                "crypto_sources_win_x86_64" => check_env(CryptoTarget::WinX86_64),
-                "ssl_c_sources" => Context::Ssl,
-                "ssl_cc_sources" => Context::Ignore, // TODO: support C++ files
-                "ssl_headers" => Context::Ignore,
-                "ssl_internal_headers" => Context::Ignore,
-                "ssl_sources" => Context::Ignore,
-                "tool_sources" => Context::Ignore,
-                "tool_headers" => Context::Ignore,
-                s => panic!("unknown source file context {:?}", s),
-            };
-        } else {
-            if line.starts_with("    \"") {
-                // add file to respective sources list
-                match ctx {
-                    Context::Ignore => continue,
-                    Context::Asm => asm_src.push(get_filename(&mut line)),
-                    Context::Crypto => crypto_src.push(get_filename(&mut line)),
-                    Context::Ssl => ssl_src.push(get_filename(&mut line)),
-                    _ => panic!("how did that happen?! {:?}", ctx),
-                };
-            } else if line == "]" {
-                // context end
-                ctx = Context::None;
+/// Compiles and links the static libs. With a prefix and the directory
+/// containing the prefix headers, all symbols are prefixed; without, the
+/// libs are not linked and their file names are returned to be inspected.
+fn compile(target: &Option<CryptoTarget>,
+           sources: &Sources,
+           prefix: Option<(&str, &Path)>)
+           -> Vec<&'static str> {
+    let prefixing = env::var_os("CARGO_FEATURE_PREFIX_SYMBOLS").is_some();
+    let (c_lib, cc_lib, asm_lib) = if prefixing && prefix.is_none() {
+        ("libboringssl_unprefixed.a",
+         "libboringssl_cc_unprefixed.a",
+         "libboringssl_asm_unprefixed.a")
+    } else {
+        ("libboringssl.a", "libboringssl_cc.a", "libboringssl_asm.a")
+    };
+    // The prefix headers are included explicitly rather than through base.h,
+    // as older BoringSSL doesn't include them from there.
+    let configure = |config: &mut gcc::Config, header: &str| {
+        config.cargo_metadata(!prefixing || prefix.is_some());
+        if let Some((prefix, include_dir)) = prefix {
+            config.include(include_dir)
+                .define("BORINGSSL_PREFIX", Some(prefix))
+                .flag("-include")
+                .flag(&include_dir.join(header).to_string_lossy());
+        }
+    };
+
+    // compile config
+    let mut boringssl = config(target);
+    boringssl.flag("-std=c11");
+    configure(&mut boringssl, "boringssl_prefix_symbols.h");
+
+    // add source files
+    for src in &sources.c {
+        boringssl.file(src);
+    }
+
+    // compile and link static lib
+    boringssl.compile(c_lib);
+    let mut libs = vec![c_lib];
+
+    // The C++ part of libssl is compiled into a separate static lib, as it
+    // needs different flags. Both are bundled into the rlib, so references
+    // between them resolve regardless of link order.
+    if !sources.cc.is_empty() {
+        let mut boringssl_cc = config(target);
+        boringssl_cc.cpp(true)
+            .cpp_link_stdlib(cpp_stdlib().as_deref())
+            .flag(&cpp_standard())
+            .flag("-fno-exceptions")
+            .flag("-fno-rtti");
+        configure(&mut boringssl_cc, "boringssl_prefix_symbols.h");
+        for src in &sources.cc {
+            boringssl_cc.file(src);
+        }
+        boringssl_cc.compile(cc_lib);
+        libs.push(cc_lib);
+    }
+
+    // The assembly is a separate lib too, as it is renamed by another
+    // header. The NASM sources of Windows are assembled separately.
+    if !sources.asm.is_empty() {
+        let mut boringssl_asm = config(target);
+        configure(&mut boringssl_asm, "boringssl_prefix_symbols_asm.h");
+        for src in &sources.asm {
+            if src.ends_with(".asm") {
+                boringssl_asm.object(assemble_nasm(target, src));
             } else {
-                panic!("unable to parse line: {:?}", line);
+                boringssl_asm.file(src);
             }
         }
+        boringssl_asm.compile(asm_lib);
+        libs.push(asm_lib);
     }
+    libs
+}
 
-    // compile config
-    let mut boringssl = gcc::Config::new();
-    boringssl.include("third_party/boringssl/src/include")
+/// Assembles one of the NASM sources of the Windows targets with nasm (or the
+/// NASM environment variable) and returns the path of the object file.
+fn assemble_nasm(target: &Option<CryptoTarget>, src: &str) -> PathBuf {
+    let format = match *target {
+        Some(CryptoTarget::WinX86) => "win32",
+        _ => "win64",
+    };
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap()).join("nasm");
+    fs::create_dir_all(&out_dir).unwrap();
+    let obj = out_dir.join(Path::new(src).with_extension("obj").file_name().unwrap());
+    let nasm = env::var("NASM").unwrap_or_else(|_| "nasm".to_string());
+    let status = Command::new(&nasm).arg("-f").arg(format).arg("-o").arg(&obj).arg(src).status();
+    let status = match status {
+        Err(why) => panic!("couldn't run {}: {}", nasm, why),
+        Ok(status) => status,
+    };
+    if !status.success() {
+        panic!("{} failed on {}", nasm, src);
+    }
+    obj
+}
+
+/// Returns the prefix for the prefix-symbols feature, which can be set with
+/// the BORINGSSL_PREFIX environment variable. It is passed on to ffi.rs.
+fn symbol_prefix() -> String {
+    let prefix = env::var("BORINGSSL_PREFIX").unwrap_or_else(|_| DEFAULT_PREFIX.to_string());
+    if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') ||
+       prefix.starts_with(|c: char| c.is_ascii_digit()) {
+        panic!("BORINGSSL_PREFIX must be a C identifier, not {:?}", prefix);
+    }
+    println!("cargo:rustc-env=BORINGSSL_PREFIX={}", prefix);
+    prefix
+}
+
+/// Returns the external symbols defined in the static lib, as listed by nm
+/// (or the NM environment variable).
+fn read_symbols(lib: &Path) -> Vec<String> {
+    let nm = env::var("NM").unwrap_or_else(|_| "nm".to_string());
+    let output = match Command::new(&nm).arg("-g").arg("-P").arg(lib).output() {
+        Err(why) => panic!("couldn't run {}: {}", nm, why),
+        Ok(output) => output,
+    };
+    if !output.status.success() {
+        panic!("{} failed on {}: {}",
+               nm,
+               lib.display(),
+               String::from_utf8_lossy(&output.stderr));
+    }
+    let apple = env::var("TARGET").unwrap().contains("apple");
+    let mut symbols = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        // "<name> <type> [<value> <size>]", or "<lib>[<object>]:" headers
+        let mut words = line.split_whitespace();
+        let (name, kind) = match (words.next(), words.next()) {
+            (Some(name), Some(kind)) => (name, kind),
+            _ => continue,
+        };
+        if kind == "U" || kind == "w" || kind == "v" {
+            continue;
+        }
+        // Mach-O prefixes C symbols with an underscore
+        let name = if !apple {
+            name
+        } else if let Some(stripped) = name.strip_prefix('_') {
+            stripped
+        } else {
+            continue;
+        };
+        // skip C++ and compiler-internal symbols, which can't be renamed by
+        // a macro and don't clash with OpenSSL's
+        if name.starts_with("_Z") || name.starts_with("__") ||
+           !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            continue;
+        }
+        symbols.push(name.to_string());
+    }
+    symbols
+}
+
+/// Panics if the static libs define symbols without the prefix, e.g. because
+/// a source didn't see the prefix header.
+fn check_prefixed(libs: &[&str], prefix: &str) {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let prefix = format!("{}_", prefix);
+    let mut unprefixed = Vec::new();
+    for lib in libs {
+        unprefixed.extend(read_symbols(&out_dir.join(lib))
+            .into_iter()
+            .filter(|symbol| !symbol.starts_with(&prefix)));
+    }
+    if !unprefixed.is_empty() {
+        panic!("{} symbols are not prefixed with {}, e.g. {:?}",
+               unprefixed.len(),
+               prefix,
+               &unprefixed[..unprefixed.len().min(10)]);
+    }
+}
+
+/// Writes the headers BoringSSL includes when BORINGSSL_PREFIX is defined,
+/// which rename symbols in C and assembly sources respectively.
+fn write_prefix_headers(include_dir: &Path, symbols: &[String]) {
+    fs::create_dir_all(include_dir).unwrap();
+
+    let mut c = String::from("#define BORINGSSL_ADD_PREFIX(a, b) BORINGSSL_ADD_PREFIX_INNER(a, b)\n\
+                              #define BORINGSSL_ADD_PREFIX_INNER(a, b) a ## _ ## b\n\n");
+    for symbol in symbols {
+        c += &format!("#define {0} BORINGSSL_ADD_PREFIX(BORINGSSL_PREFIX, {0})\n", symbol);
+    }
+    write_file(&include_dir.join("boringssl_prefix_symbols.h"), &c);
+
+    // Mach-O symbols carry an underscore in assembly
+    let mut asm = String::from("#if defined(__APPLE__)\n\
+                                #define BORINGSSL_ADD_PREFIX_MAC(a, b) \
+                                BORINGSSL_ADD_PREFIX_INNER_MAC(a, b)\n\
+                                #define BORINGSSL_ADD_PREFIX_INNER_MAC(a, b) _ ## a ## _ ## b\n");
+    for symbol in symbols {
+        asm += &format!("#define _{0} BORINGSSL_ADD_PREFIX_MAC(BORINGSSL_PREFIX, {0})\n",
+                        symbol);
+    }
+    asm += "#else\n";
+    asm += &c;
+    asm += "#endif\n";
+    write_file(&include_dir.join("boringssl_prefix_symbols_asm.h"), &asm);
+}
+
+fn write_file(path: &Path, contents: &str) {
+    let mut file = match File::create(path) {
+        Err(why) => panic!("couldn't create {}: {}", path.display(), why),
+        Ok(file) => file,
+    };
+    file.write_all(contents.as_bytes()).unwrap();
+}
+
+/// Returns the compile config shared by the C and C++ sources.
+fn config(target: &Option<CryptoTarget>) -> gcc::Config {
+    let mut config = gcc::Config::new();
+    config.include("third_party/boringssl/src/include")
         .define("BORINGSSL_IMPLEMENTATION", None)
         //.define("BORINGSSL_NO_STATIC_INITIALIZER", None) // not yet
         .define("OPENSSL_SMALL", None)
-        .define("_XOPEN_SOURCE", Some("700"))
-        .flag("-std=c11");
+        .define("_XOPEN_SOURCE", Some("700"));
+    if target.is_none() {
+        config.define("OPENSSL_NO_ASM", None);
+    }
+    config
+}
 
-    // add source files
-    for src in &asm_src {
-        boringssl.file(src);
+/// Returns the -std flag for the C++ sources, the standard BoringSSL's
+/// CMakeLists.txt builds with, either through CMAKE_CXX_STANDARD or a -std
+/// flag. It defaults to C++17, which recent BoringSSL requires.
+fn cpp_standard() -> String {
+    let cmake = Path::new(GENERATED_DIR).join("src/CMakeLists.txt");
+    let mut contents = String::new();
+    if let Ok(mut file) = File::open(&cmake) {
+        file.read_to_string(&mut contents).unwrap();
     }
-    for src in &crypto_src {
-        boringssl.file(src);
+    for line in contents.lines() {
+        let standard = if let Some(i) = line.find("CMAKE_CXX_STANDARD ") {
+            line[i + "CMAKE_CXX_STANDARD ".len()..].split(|c: char| !c.is_ascii_digit()).next()
+        } else if let Some(i) = line.find("-std=c++") {
+            line[i + "-std=c++".len()..].split(|c: char| !c.is_ascii_digit()).next()
+        } else {
+            None
+        };
+        match standard {
+            Some(n) if !n.is_empty() => return format!("-std=c++{}", n),
+            _ => {}
+        }
     }
-    for src in &ssl_src {
-        boringssl.file(src);
+    "-std=c++17".to_string()
+}
+
+/// Returns the C++ runtime to link, without the lib prefix.
+/// It can be set with the CXXSTDLIB environment variable, e.g. to "c++" for
+/// libc++ (together with CXXFLAGS=-stdlib=libc++ for clang), or to an empty
+/// string to link none.
+fn cpp_stdlib() -> Option<String> {
+    if let Ok(stdlib) = env::var("CXXSTDLIB") {
+        if stdlib.is_empty() {
+            return None;
+        }
+        return Some(stdlib);
     }
 
-    // compile and link static lib
-    boringssl.compile("libboringssl.a");
+    let target = env::var("TARGET").unwrap();
+    if target.contains("msvc") {
+        None
+    } else if target.contains("android") {
+        Some("c++_shared".to_string())
+    } else if target.contains("darwin") || target.contains("freebsd") {
+        Some("c++".to_string())
+    } else {
+        Some("stdc++".to_string())
+    }
+}
+
+/// Builds BoringSSL's test binaries for the boringssl-tests feature, linked
+/// against the static libs the crate uses, and writes a #[test] running each
+/// of them.
+fn build_tests(target: &Option<CryptoTarget>, libs: &[&str], prefix: Option<(&str, &Path)>) {
+    let tests_path = Path::new(GENERATED_DIR).join("BUILD.generated_tests.bzl");
+    if !tests_path.exists() || env::var("TARGET").unwrap().contains("msvc") {
+        println!("cargo:warning=BoringSSL's tests can't be built, {} is missing or the \
+                  target is msvc",
+                 tests_path.display());
+        write_tests(&[]);
+        return;
+    }
+    let tokens = tokenize(&read_file(&tests_path));
+    let mut tests = parse_cc_tests(&tokens);
+    let lists = parse_lists(&tokens);
+    let list = |name: &str| -> Vec<String> {
+        lists.iter().filter(|l| l.0 == name).flat_map(|l| l.1.clone()).collect()
+    };
+
+    let test_config = |cpp: bool| {
+        let mut config = config(target);
+        config.cargo_metadata(false).include(GTEST_DIR.to_string() + "/include");
+        if cpp {
+            config.cpp(true).flag(&cpp_standard());
+        } else {
+            config.flag("-std=c11");
+        }
+        if let Some((prefix, include_dir)) = prefix {
+            config.include(include_dir)
+                .define("BORINGSSL_PREFIX", Some(prefix))
+                .flag("-include")
+                .flag(&include_dir.join("boringssl_prefix_symbols.h").to_string_lossy());
+        }
+        config
+    };
+    let compile_lib = |name: &str, srcs: &[String], copts: &[String]| {
+        for &cpp in &[false, true] {
+            let mut config = test_config(cpp);
+            let mut empty = true;
+            for src in srcs {
+                let is_cpp = src.ends_with(".cc") || src.ends_with(".cpp");
+                if is_cpp == cpp && (is_cpp || src.ends_with(".c")) {
+                    config.file(format!("{}/{}", GENERATED_DIR, src));
+                    empty = false;
+                }
+            }
+            for copt in copts {
+                config.flag(copt);
+            }
+            if !empty {
+                config.compile(&format!("lib{}{}.a", name, if cpp { "_cc" } else { "" }));
+            }
+        }
+    };
+
+    // test support and, for the crypto_test and ssl_test binaries, googletest
+    let mut test_libs = vec!["boringssl_test_support", "boringssl_test_support_cc"];
+    compile_lib("boringssl_test_support", &list("test_support_sources"), &[]);
+    if Path::new(GTEST_DIR).join("src/gtest-all.cc").exists() {
+        let mut gtest = test_config(true);
+        gtest.include(GTEST_DIR).file(GTEST_DIR.to_string() + "/src/gtest-all.cc");
+        gtest.compile("libgtest.a");
+        test_libs.push("gtest");
+        for name in &["crypto_test", "ssl_test"] {
+            tests.push(TestBinary {
+                name: name.to_string(),
+                srcs: list(&format!("{}_sources", name)),
+                args: Vec::new(),
+                copts: Vec::new(),
+                gtest: true,
+            });
+        }
+    } else {
+        println!("cargo:warning=googletest not found in {}, skipping crypto_test and ssl_test",
+                 GTEST_DIR);
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let bin_dir = out_dir.join("boringssl_tests");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let apple = env::var("TARGET").unwrap().contains("apple");
+    let mut built = Vec::new();
+    let mut compiled: Vec<(Vec<String>, String)> = Vec::new();
+    for test in tests {
+        // tests like aead_test only differ in their args
+        let main_lib = match compiled.iter().find(|c| c.0 == test.srcs) {
+            Some(c) => c.1.clone(),
+            None => {
+                let name = format!("boringssl_test_{}", compiled.len());
+                compile_lib(&name, &test.srcs, &test.copts);
+                compiled.push((test.srcs.clone(), name.clone()));
+                name
+            }
+        };
+
+        // the libs are archives, so each lib is only linked if it exists;
+        // the group resolves the references between them
+        let bin = bin_dir.join(&test.name);
+        let mut cmd = test_config(true).get_compiler().to_command();
+        cmd.arg("-o").arg(&bin).arg("-L").arg(&out_dir);
+        if !apple {
+            cmd.arg("-Wl,--start-group");
+        }
+        let main_libs = [main_lib.clone(), main_lib + "_cc"];
+        let all_libs = main_libs.iter()
+            .map(|l| l.as_str())
+            .chain(test_libs.iter().cloned())
+            .chain(libs.iter().map(|l| &l[3..l.len() - 2]));
+        for lib in all_libs {
+            if out_dir.join(format!("lib{}.a", lib)).exists() {
+                cmd.arg(format!("-l{}", lib));
+            }
+        }
+        if !apple {
+            cmd.arg("-Wl,--end-group");
+        }
+        cmd.arg("-lpthread");
+        run(cmd);
+        built.push((test, bin));
+    }
+    write_tests(&built);
+}
+
+/// The googletest sources BoringSSL ships for its crypto_test and ssl_test.
+const GTEST_DIR: &str = "third_party/boringssl/src/third_party/googletest";
+
+fn run(mut cmd: Command) {
+    let output = match cmd.output() {
+        Err(why) => panic!("couldn't run {:?}: {}", cmd, why),
+        Ok(output) => output,
+    };
+    if !output.status.success() {
+        panic!("{:?} failed: {}{}",
+               cmd,
+               String::from_utf8_lossy(&output.stdout),
+               String::from_utf8_lossy(&output.stderr));
+    }
+}
+
+/// Writes OUT_DIR/boringssl_tests.rs, which tests/boringssl.rs includes, with
+/// a #[test] for each test binary.
+fn write_tests(tests: &[(TestBinary, PathBuf)]) {
+    let data_dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join(GENERATED_DIR);
+    let mut rs = String::new();
+    for (test, bin) in tests {
+        // "$(location file)" args refer to data files
+        let args: Vec<String> = test.args
+            .iter()
+            .map(|arg| if arg.starts_with("$(location ") && arg.ends_with(')') {
+                data_dir.join(&arg[11..arg.len() - 1]).display().to_string()
+            } else {
+                arg.clone()
+            })
+            .collect();
+        let name: String = test.name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        rs += &format!("#[test]\nfn {}() {{\n    run({:?}, &{:?}, {});\n}}\n\n",
+                       name,
+                       bin.display().to_string(),
+                       args,
+                       test.gtest);
+    }
+    let path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("boringssl_tests.rs");
+    write_file(&path, &rs);
 }
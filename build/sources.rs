@@ -0,0 +1,256 @@
+// The parsers of the source lists generate_build_files.py writes, included by
+// build.rs and by tests/build_sources.rs, which tests them against the files
+// in tests/fixtures.
+
+/// The files to compile, by language.
+#[derive(Debug, Default)]
+struct Sources {
+    asm: Vec<String>,
+    c: Vec<String>,
+    cc: Vec<String>,
+    /// The names of the lists which were neither compiled nor known to be
+    /// irrelevant.
+    unknown: Vec<String>,
+}
+
+/// Selects the sources to compile from the lists of the generated file. The
+/// assembly of platform, e.g. "linux_x86_64", is compiled, together with the
+/// platform-independent lists of assembly, whose files check the platform
+/// themselves; without a platform, none is.
+fn select_sources(lists: Vec<(String, Vec<String>)>, platform: Option<&str>) -> Sources {
+    let windows = platform.is_some_and(|p| p.starts_with("win"));
+    let mut sources = Sources::default();
+    for (name, files) in lists {
+        // e.g. crypto_sources_linux_x86_64 in bazel, crypto_linux_x86_64 in
+        // json and CRYPTO_SOURCES_LINUX_X86_64 in cmake, or crypto_sources_asm
+        // and crypto_sources_nasm (Windows) for all platforms
+        let lower = name.to_lowercase();
+        let words: Vec<&str> = lower.split('_').filter(|w| *w != "sources").collect();
+        if words.iter().any(|w| IGNORED_WORDS.contains(w)) {
+            continue;
+        }
+        let asm = match words.split_first() {
+            // ssl_sources may list the files of ssl_c_sources and
+            // ssl_cc_sources again, duplicates are removed below
+            Some((&"ssl", rest)) if rest.is_empty() || rest == ["c"] || rest == ["cc"] => false,
+            Some((&"crypto", rest)) | Some((&"bcm", rest)) if rest.is_empty() => false,
+            Some((&"crypto", rest)) | Some((&"bcm", rest)) if rest == ["asm"] => {
+                match platform {
+                    Some(_) if !windows => true,
+                    _ => continue,
+                }
+            }
+            Some((&"crypto", rest)) | Some((&"bcm", rest)) if rest == ["nasm"] => {
+                match platform {
+                    Some(_) if windows => true,
+                    _ => continue,
+                }
+            }
+            Some((&"crypto", rest)) if is_platform(rest) => {
+                match platform {
+                    Some(p) if rest.join("_") == p => true,
+                    _ => continue,
+                }
+            }
+            _ => {
+                sources.unknown.push(name);
+                continue;
+            }
+        };
+        for file in files {
+            let list = match ::std::path::Path::new(&file).extension().and_then(|e| e.to_str()) {
+                Some("c") => &mut sources.c,
+                Some("cc") | Some("cpp") => &mut sources.cc,
+                Some("S") | Some("s") | Some("asm") if asm => &mut sources.asm,
+                _ => continue,
+            };
+            list.push(file);
+        }
+    }
+    for list in &mut [&mut sources.asm, &mut sources.c, &mut sources.cc] {
+        list.sort();
+        list.dedup();
+    }
+    sources
+}
+
+/// Returns whether words name the platform of a list of assembly, e.g.
+/// ["linux", "x86_64"].
+fn is_platform(words: &[&str]) -> bool {
+    matches!(words.first(),
+             Some(&"linux") | Some(&"mac") | Some(&"win") | Some(&"ios") | Some(&"apple"))
+}
+
+/// Source lists whose name contains one of these are not compiled, e.g.
+/// crypto_internal_headers or ssl_test_sources.
+const IGNORED_WORDS: &[&str] = &["headers", "test", "tool", "fuzz", "support"];
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    /// A bare word, e.g. a bazel variable or a cmake argument.
+    Word(String),
+    /// A quoted string.
+    Str(String),
+    Punct(char),
+}
+
+/// Splits the generated bazel, json or cmake file into tokens, skipping
+/// comments and whitespace.
+fn tokenize(s: &str) -> Vec<Token> {
+    const PUNCT: &str = "=:,[](){}";
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        } else if c == '#' {
+            while chars.peek().is_some_and(|&c| c != '\n') {
+                chars.next();
+            }
+        } else if c == '"' {
+            let mut string = String::new();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => string.extend(chars.next()),
+                    c => string.push(c),
+                }
+            }
+            tokens.push(Token::Str(string));
+        } else if PUNCT.contains(c) {
+            tokens.push(Token::Punct(c));
+        } else {
+            let mut word = c.to_string();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || PUNCT.contains(c) || c == '"' || c == '#' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+    tokens
+}
+
+/// Parses the `name = ["file", ...]` lists of bazel and the
+/// `"name": ["file", ...]` members of json. Anything else is skipped.
+fn parse_lists(tokens: &[Token]) -> Vec<(String, Vec<String>)> {
+    let mut lists = Vec::new();
+    let mut i = 0;
+    while i + 2 < tokens.len() {
+        let name = match (&tokens[i], &tokens[i + 1], &tokens[i + 2]) {
+            (&Token::Word(ref name), &Token::Punct('='), &Token::Punct('[')) |
+            (&Token::Str(ref name), &Token::Punct(':'), &Token::Punct('[')) => name.clone(),
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        i += 3;
+        let mut files = Vec::new();
+        while i < tokens.len() {
+            match tokens[i] {
+                Token::Str(ref file) => files.push(file.clone()),
+                Token::Punct(',') => {}
+                _ => break,
+            }
+            i += 1;
+        }
+        lists.push((name, files));
+    }
+    lists
+}
+
+/// Parses the `set(NAME file ...)` commands of cmake. Anything else is
+/// skipped.
+fn parse_cmake(tokens: &[Token]) -> Vec<(String, Vec<String>)> {
+    let mut lists = Vec::new();
+    let mut i = 0;
+    while i + 2 < tokens.len() {
+        let name = match (&tokens[i], &tokens[i + 1], &tokens[i + 2]) {
+            (Token::Word(set), &Token::Punct('('), Token::Word(name))
+                if set.eq_ignore_ascii_case("set") => name.clone(),
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        i += 3;
+        let mut files = Vec::new();
+        while i < tokens.len() {
+            match tokens[i] {
+                Token::Word(ref file) | Token::Str(ref file) => files.push(file.clone()),
+                _ => break,
+            }
+            i += 1;
+        }
+        lists.push((name, files));
+    }
+    lists
+}
+
+/// A test binary listed in BUILD.generated_tests.bzl.
+struct TestBinary {
+    name: String,
+    srcs: Vec<String>,
+    args: Vec<String>,
+    copts: Vec<String>,
+    /// Whether it is a googletest binary, which doesn't print PASS.
+    gtest: bool,
+}
+
+/// Parses the cc_test rules of BUILD.generated_tests.bzl. Only the name, srcs,
+/// args and copts strings are read, as the test support sources and the libs
+/// are the same for all tests.
+fn parse_cc_tests(tokens: &[Token]) -> Vec<TestBinary> {
+    let mut tests = Vec::new();
+    let mut i = 0;
+    while i + 1 < tokens.len() {
+        match (&tokens[i], &tokens[i + 1]) {
+            (Token::Word(rule), &Token::Punct('('))
+                if rule == "native.cc_test" || rule == "cc_test" => {}
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+        let mut test = TestBinary {
+            name: String::new(),
+            srcs: Vec::new(),
+            args: Vec::new(),
+            copts: Vec::new(),
+            gtest: false,
+        };
+        let mut key = String::new();
+        let mut depth = 0;
+        while i < tokens.len() {
+            match tokens[i] {
+                Token::Punct(')') if depth == 0 => break,
+                Token::Punct('(') | Token::Punct('[') => depth += 1,
+                Token::Punct(')') | Token::Punct(']') => depth -= 1,
+                Token::Word(ref word) if depth == 0 &&
+                                         tokens.get(i + 1) == Some(&Token::Punct('=')) => {
+                    key = word.clone()
+                }
+                Token::Str(ref s) => {
+                    match key.as_ref() {
+                        "name" => test.name = s.clone(),
+                        "srcs" => test.srcs.push(s.clone()),
+                        "args" => test.args.push(s.clone()),
+                        "copts" => test.copts.push(s.clone()),
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        if !test.name.is_empty() && !test.srcs.is_empty() {
+            tests.push(test);
+        }
+    }
+    tests
+}
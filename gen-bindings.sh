@@ -0,0 +1,40 @@
+#!/bin/sh
+# Generates src/sys.rs, the bindings exposed as boringssl::sys, from
+# BoringSSL's headers with bindgen (cargo install bindgen-cli).
+# Run by roll.sh after updating BoringSSL.
+
+cd "$(dirname "$0")" || exit 1
+
+INCLUDE=third_party/boringssl/src/include
+if [ ! -f $INCLUDE/openssl/ssl.h ]; then
+    echo "$INCLUDE/openssl/ssl.h not found, is the submodule checked out?"
+    exit 1
+fi
+
+REV=$(git -C third_party/boringssl/src rev-parse --short HEAD) || exit 1
+
+mkdir -p target || exit 1
+WRAPPER=target/bindings.h
+cat > $WRAPPER <<HEADERS
+#include <openssl/ssl.h>
+#include <openssl/x509.h>
+#include <openssl/x509v3.h>
+#include <openssl/evp.h>
+#include <openssl/err.h>
+HEADERS
+
+# The headers these build on are included for what ffi.rs uses from them.
+# Types which ffi.rs treats as opaque are generated as opaque blobs.
+echo "generating bindings for $REV..."
+bindgen $WRAPPER -o src/sys.rs \
+    --raw-line "// Generated by gen-bindings.sh from BoringSSL $REV, do not edit." \
+    --allowlist-file ".*/openssl/(ssl|ssl3|tls1|x509|x509v3|x509_vfy|evp|err|bio|asn1|pem|stack|nid|rsa)\.h" \
+    --opaque-type "(ssl|ssl_ctx|ssl_method|ssl_cipher|ssl_session|bio|bio_method)_st" \
+    --opaque-type "(x509|X509_name|X509_crl|x509_store|x509_store_ctx|X509_VERIFY_PARAM|asn1_string)_st" \
+    --opaque-type "(evp_pkey|evp_pkey_ctx|env_md|env_md_ctx|stack)_st" \
+    --default-enum-style consts \
+    --ctypes-prefix "::std::os::raw" \
+    --no-prepend-enum-name \
+    -- -I$INCLUDE || exit 1
+
+echo "done!"
@@ -22,11 +22,17 @@ echo "generating source files..."
 cd .. || exit 1
 python src/util/generate_build_files.py bazel || exit 1
 
+echo "generating bindings..."
+(cd ../.. && ./gen-bindings.sh) || exit 1
+
+echo "checking bindings..."
+(cd ../.. && cargo test) || exit 1
+
 echo "commiting..."
 SHORT_REV=$(echo $REMOTE | cut -c1-6)
 COMMIT_MSG="Roll $SHORT_REV"
 
-git add . || exit 1
+git add . ../../src/sys.rs || exit 1
 git commit -m "$COMMIT_MSG" || exit 1
 
 echo "done!"
@@ -0,0 +1,197 @@
+//! Checks the hand-written declarations in ffi.rs against the bindings
+//! generated from BoringSSL's headers into sys.rs by gen-bindings.sh, so that
+//! a roll which changes them fails to build or `cargo test`. The signatures of
+//! the declared functions are compared by the boringssl! macro, the layouts
+//! and constants by the tests below.
+
+use std::marker::PhantomData;
+use std::mem;
+use std::os::raw::c_void;
+
+/// Maps a type in a function signature to what the ABI sees of it, so that
+/// the signatures in ffi.rs and sys.rs can be compared although they name
+/// their types differently: integers are compared by size only, pointers
+/// (e.g. *mut SSL, which is *mut c_void in ffi.rs and *mut ssl_st in sys.rs)
+/// compare equal whatever they point to, and callbacks by their signatures.
+pub trait Erase {
+    type Erased;
+}
+
+/// The erased type of an integer of N bytes.
+pub struct Int<N>(PhantomData<N>);
+
+/// The erased type of a function or callback returning R::Erased and taking
+/// the erased arguments A.
+pub struct Fn<R, A>(PhantomData<(R, A)>);
+
+macro_rules! erase_ints {
+    ($($t:ty),*) => {
+        $(
+            impl Erase for $t {
+                type Erased = Int<[u8; mem::size_of::<$t>()]>;
+            }
+        )*
+    }
+}
+
+erase_ints!(i8, u8, i16, u16, i32, u32, i64, u64, isize, usize);
+
+impl Erase for () {
+    type Erased = ();
+}
+
+impl<T: ?Sized> Erase for *const T {
+    type Erased = *const c_void;
+}
+
+impl<T: ?Sized> Erase for *mut T {
+    type Erased = *const c_void;
+}
+
+/// Callbacks are nullable function pointers, which bindgen always wraps in an
+/// Option.
+impl<F: Erase> Erase for Option<F> {
+    type Erased = F::Erased;
+}
+
+macro_rules! erase_fns {
+    ($($arg:ident),*) => {
+        impl<R: Erase, $($arg: Erase),*> Erase for extern "C" fn($($arg),*) -> R {
+            type Erased = Fn<R::Erased, ($($arg::Erased,)*)>;
+        }
+
+        impl<R: Erase, $($arg: Erase),*> Erase for unsafe extern "C" fn($($arg),*) -> R {
+            type Erased = Fn<R::Erased, ($($arg::Erased,)*)>;
+        }
+    }
+}
+
+erase_fns!();
+erase_fns!(A);
+erase_fns!(A, B);
+erase_fns!(A, B, C);
+erase_fns!(A, B, C, D);
+erase_fns!(A, B, C, D, E);
+erase_fns!(A, B, C, D, E, F);
+erase_fns!(A, B, C, D, E, F, G);
+erase_fns!(A, B, C, D, E, F, G, H);
+erase_fns!(A, B, C, D, E, F, G, H, I);
+
+/// Fails to compile unless declared and generated have the same signature
+/// once erased.
+pub fn same_signature<D, G>(_declared: D, _generated: G)
+    where D: Erase,
+          G: Erase<Erased = D::Erased>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use ffi;
+    use sys;
+
+    macro_rules! check_layouts {
+        ($($name:ident),*) => {
+            $(
+                assert_eq!((mem::size_of::<ffi::$name>(), mem::align_of::<ffi::$name>()),
+                           (mem::size_of::<sys::$name>(), mem::align_of::<sys::$name>()),
+                           "layout of {}",
+                           stringify!($name));
+            )*
+        }
+    }
+
+    macro_rules! check_constants {
+        ($($name:ident),*) => {
+            $(
+                assert_eq!(ffi::$name as i64, sys::$name as i64, "value of {}", stringify!($name));
+            )*
+        }
+    }
+
+    /// The structs passed to BoringSSL have the size and alignment of the
+    /// generated ones, whose field offsets are checked by bindgen's layout tests.
+    #[test]
+    fn layouts() {
+        check_layouts!(SSL_CLIENT_HELLO, timeval, GENERAL_NAME);
+    }
+
+    #[cfg(boringssl_private_key_method)]
+    #[test]
+    fn private_key_method() {
+        check_layouts!(SSL_PRIVATE_KEY_METHOD);
+        check_constants!(ssl_private_key_success, ssl_private_key_retry, ssl_private_key_failure);
+    }
+
+    #[cfg(feature = "quic")]
+    #[test]
+    fn quic() {
+        check_layouts!(SSL_QUIC_METHOD);
+        check_constants!(ssl_encryption_initial, ssl_encryption_early_data,
+                         ssl_encryption_handshake, ssl_encryption_application);
+    }
+
+    #[test]
+    fn constants() {
+        check_constants!(SSL_SESS_CACHE_OFF, SSL_SESS_CACHE_CLIENT, SSL_SESS_CACHE_SERVER,
+                         SSL_SESS_CACHE_BOTH, TLS1_VERSION, TLS1_1_VERSION, TLS1_2_VERSION,
+                         TLS1_3_VERSION, DTLS1_VERSION, DTLS1_2_VERSION, SSL_VERIFY_NONE,
+                         SSL_VERIFY_PEER, SSL_VERIFY_FAIL_IF_NO_PEER_CERT,
+                         SSL_VERIFY_PEER_IF_NO_OBC, ssl_select_cert_success, ssl_select_cert_retry,
+                         ssl_select_cert_error, TLSEXT_TYPE_server_name,
+                         TLSEXT_TYPE_application_layer_protocol_negotiation,
+                         OPENSSL_NPN_NEGOTIATED, OPENSSL_NPN_NO_OVERLAP, SSL_TLSEXT_ERR_OK,
+                         SSL_TLSEXT_ERR_ALERT_WARNING, SSL_TLSEXT_ERR_ALERT_FATAL,
+                         SSL_TLSEXT_ERR_NOACK, TLSEXT_NAMETYPE_host_name, SSL_CB_LOOP, SSL_CB_EXIT,
+                         SSL_CB_READ, SSL_CB_WRITE, SSL_CB_ALERT, SSL_CB_READ_ALERT,
+                         SSL_CB_WRITE_ALERT, SSL_CB_HANDSHAKE_START, SSL_CB_HANDSHAKE_DONE,
+                         SSL3_RT_CHANGE_CIPHER_SPEC, SSL3_RT_ALERT, SSL3_RT_HANDSHAKE,
+                         SSL3_RT_APPLICATION_DATA, SSL3_RT_HEADER, SSL3_AL_WARNING, SSL3_AL_FATAL,
+                         SSL_AD_CLOSE_NOTIFY, SSL_AD_UNEXPECTED_MESSAGE, SSL_AD_BAD_RECORD_MAC,
+                         SSL_AD_DECRYPTION_FAILED, SSL_AD_RECORD_OVERFLOW,
+                         SSL_AD_DECOMPRESSION_FAILURE, SSL_AD_HANDSHAKE_FAILURE,
+                         SSL_AD_NO_CERTIFICATE, SSL_AD_BAD_CERTIFICATE,
+                         SSL_AD_UNSUPPORTED_CERTIFICATE, SSL_AD_CERTIFICATE_REVOKED,
+                         SSL_AD_CERTIFICATE_EXPIRED, SSL_AD_CERTIFICATE_UNKNOWN,
+                         SSL_AD_ILLEGAL_PARAMETER, SSL_AD_UNKNOWN_CA, SSL_AD_ACCESS_DENIED,
+                         SSL_AD_DECODE_ERROR, SSL_AD_DECRYPT_ERROR, SSL_AD_EXPORT_RESTRICTION,
+                         SSL_AD_PROTOCOL_VERSION, SSL_AD_INSUFFICIENT_SECURITY,
+                         SSL_AD_INTERNAL_ERROR, SSL_AD_INAPPROPRIATE_FALLBACK,
+                         SSL_AD_USER_CANCELLED, SSL_AD_NO_RENEGOTIATION, SSL_AD_MISSING_EXTENSION,
+                         SSL_AD_UNSUPPORTED_EXTENSION, SSL_AD_CERTIFICATE_UNOBTAINABLE,
+                         SSL_AD_UNRECOGNIZED_NAME, SSL_AD_BAD_CERTIFICATE_STATUS_RESPONSE,
+                         SSL_AD_BAD_CERTIFICATE_HASH_VALUE, SSL_AD_UNKNOWN_PSK_IDENTITY,
+                         SSL_AD_CERTIFICATE_REQUIRED, SSL_AD_NO_APPLICATION_PROTOCOL, BIO_NOCLOSE,
+                         BIO_CLOSE, BIO_TYPE_DESCRIPTOR, BIO_TYPE_SOURCE_SINK, BIO_TYPE_DGRAM,
+                         BIO_CTRL_FLUSH, RSA_PKCS1_PSS_PADDING, X509_V_FLAG_CRL_CHECK,
+                         X509_V_FLAG_CRL_CHECK_ALL, X509_V_OK,
+                         X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT, X509_V_ERR_UNABLE_TO_GET_CRL,
+                         X509_V_ERR_CERT_SIGNATURE_FAILURE, X509_V_ERR_CRL_SIGNATURE_FAILURE,
+                         X509_V_ERR_CERT_NOT_YET_VALID, X509_V_ERR_CERT_HAS_EXPIRED,
+                         X509_V_ERR_CRL_NOT_YET_VALID, X509_V_ERR_CRL_HAS_EXPIRED,
+                         X509_V_ERR_DEPTH_ZERO_SELF_SIGNED_CERT,
+                         X509_V_ERR_SELF_SIGNED_CERT_IN_CHAIN,
+                         X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT_LOCALLY,
+                         X509_V_ERR_UNABLE_TO_VERIFY_LEAF_SIGNATURE,
+                         X509_V_ERR_CERT_CHAIN_TOO_LONG, X509_V_ERR_CERT_REVOKED,
+                         X509_V_ERR_INVALID_CA, X509_V_ERR_INVALID_PURPOSE,
+                         X509_V_ERR_CERT_UNTRUSTED, X509_V_ERR_UNABLE_TO_GET_CRL_ISSUER,
+                         X509_V_ERR_INVALID_CALL, GEN_EMAIL, GEN_DNS, GEN_URI, GEN_IPADD,
+                         NID_rsaEncryption, NID_key_usage, NID_subject_alt_name,
+                         NID_ext_key_usage, NID_server_auth, NID_client_auth, NID_code_sign,
+                         NID_email_protect, NID_time_stamp, NID_OCSP_sign,
+                         NID_anyExtendedKeyUsage, NID_dsa, NID_X9_62_id_ecPublicKey,
+                         NID_ED25519, XN_FLAG_RFC2253,
+                         KU_DIGITAL_SIGNATURE, KU_NON_REPUDIATION, KU_KEY_ENCIPHERMENT,
+                         KU_DATA_ENCIPHERMENT, KU_KEY_AGREEMENT, KU_KEY_CERT_SIGN, KU_CRL_SIGN,
+                         KU_ENCIPHER_ONLY, KU_DECIPHER_ONLY, XKU_SSL_SERVER, XKU_SSL_CLIENT,
+                         XKU_SMIME, XKU_CODE_SIGN, XKU_OCSP_SIGN, XKU_TIMESTAMP, XKU_ANYEKU,
+                         EVP_MAX_MD_SIZE, SSL_ERROR_NONE, SSL_ERROR_SSL, SSL_ERROR_WANT_READ,
+                         SSL_ERROR_WANT_WRITE, SSL_ERROR_WANT_X509_LOOKUP, SSL_ERROR_SYSCALL,
+                         SSL_ERROR_ZERO_RETURN, SSL_ERROR_WANT_CONNECT, SSL_ERROR_WANT_ACCEPT,
+                         SSL_ERROR_WANT_CHANNEL_ID_LOOKUP, SSL_ERROR_PENDING_SESSION,
+                         SSL_ERROR_PENDING_CERTIFICATE, SSL_ERROR_WANT_PRIVATE_KEY_OPERATION);
+    }
+}
@@ -0,0 +1,255 @@
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_int;
+use std::str;
+
+use ffi;
+
+/// The level of a TLS alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertLevel {
+    Warning,
+    Fatal,
+    Unknown(u8),
+}
+
+impl AlertLevel {
+    fn from_u8(level: u8) -> AlertLevel {
+        match level {
+            ffi::SSL3_AL_WARNING => AlertLevel::Warning,
+            ffi::SSL3_AL_FATAL => AlertLevel::Fatal,
+            n => AlertLevel::Unknown(n),
+        }
+    }
+}
+
+/// The description of a TLS alert, as defined in RFC 5246, section 7.2 and
+/// its extensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertDescription {
+    CloseNotify,
+    UnexpectedMessage,
+    BadRecordMac,
+    DecryptionFailed,
+    RecordOverflow,
+    DecompressionFailure,
+    HandshakeFailure,
+    NoCertificate,
+    BadCertificate,
+    UnsupportedCertificate,
+    CertificateRevoked,
+    CertificateExpired,
+    CertificateUnknown,
+    IllegalParameter,
+    UnknownCa,
+    AccessDenied,
+    DecodeError,
+    DecryptError,
+    ExportRestriction,
+    ProtocolVersion,
+    InsufficientSecurity,
+    InternalError,
+    InappropriateFallback,
+    UserCancelled,
+    NoRenegotiation,
+    MissingExtension,
+    UnsupportedExtension,
+    CertificateUnobtainable,
+    UnrecognizedName,
+    BadCertificateStatusResponse,
+    BadCertificateHashValue,
+    UnknownPskIdentity,
+    CertificateRequired,
+    NoApplicationProtocol,
+
+    /// An alert description unknown to this library.
+    Unknown(u8),
+}
+
+impl AlertDescription {
+    pub(crate) fn from_u8(desc: u8) -> AlertDescription {
+        match desc {
+            ffi::SSL_AD_CLOSE_NOTIFY => AlertDescription::CloseNotify,
+            ffi::SSL_AD_UNEXPECTED_MESSAGE => AlertDescription::UnexpectedMessage,
+            ffi::SSL_AD_BAD_RECORD_MAC => AlertDescription::BadRecordMac,
+            ffi::SSL_AD_DECRYPTION_FAILED => AlertDescription::DecryptionFailed,
+            ffi::SSL_AD_RECORD_OVERFLOW => AlertDescription::RecordOverflow,
+            ffi::SSL_AD_DECOMPRESSION_FAILURE => AlertDescription::DecompressionFailure,
+            ffi::SSL_AD_HANDSHAKE_FAILURE => AlertDescription::HandshakeFailure,
+            ffi::SSL_AD_NO_CERTIFICATE => AlertDescription::NoCertificate,
+            ffi::SSL_AD_BAD_CERTIFICATE => AlertDescription::BadCertificate,
+            ffi::SSL_AD_UNSUPPORTED_CERTIFICATE => AlertDescription::UnsupportedCertificate,
+            ffi::SSL_AD_CERTIFICATE_REVOKED => AlertDescription::CertificateRevoked,
+            ffi::SSL_AD_CERTIFICATE_EXPIRED => AlertDescription::CertificateExpired,
+            ffi::SSL_AD_CERTIFICATE_UNKNOWN => AlertDescription::CertificateUnknown,
+            ffi::SSL_AD_ILLEGAL_PARAMETER => AlertDescription::IllegalParameter,
+            ffi::SSL_AD_UNKNOWN_CA => AlertDescription::UnknownCa,
+            ffi::SSL_AD_ACCESS_DENIED => AlertDescription::AccessDenied,
+            ffi::SSL_AD_DECODE_ERROR => AlertDescription::DecodeError,
+            ffi::SSL_AD_DECRYPT_ERROR => AlertDescription::DecryptError,
+            ffi::SSL_AD_EXPORT_RESTRICTION => AlertDescription::ExportRestriction,
+            ffi::SSL_AD_PROTOCOL_VERSION => AlertDescription::ProtocolVersion,
+            ffi::SSL_AD_INSUFFICIENT_SECURITY => AlertDescription::InsufficientSecurity,
+            ffi::SSL_AD_INTERNAL_ERROR => AlertDescription::InternalError,
+            ffi::SSL_AD_INAPPROPRIATE_FALLBACK => AlertDescription::InappropriateFallback,
+            ffi::SSL_AD_USER_CANCELLED => AlertDescription::UserCancelled,
+            ffi::SSL_AD_NO_RENEGOTIATION => AlertDescription::NoRenegotiation,
+            ffi::SSL_AD_MISSING_EXTENSION => AlertDescription::MissingExtension,
+            ffi::SSL_AD_UNSUPPORTED_EXTENSION => AlertDescription::UnsupportedExtension,
+            ffi::SSL_AD_CERTIFICATE_UNOBTAINABLE => AlertDescription::CertificateUnobtainable,
+            ffi::SSL_AD_UNRECOGNIZED_NAME => AlertDescription::UnrecognizedName,
+            ffi::SSL_AD_BAD_CERTIFICATE_STATUS_RESPONSE => {
+                AlertDescription::BadCertificateStatusResponse
+            }
+            ffi::SSL_AD_BAD_CERTIFICATE_HASH_VALUE => AlertDescription::BadCertificateHashValue,
+            ffi::SSL_AD_UNKNOWN_PSK_IDENTITY => AlertDescription::UnknownPskIdentity,
+            ffi::SSL_AD_CERTIFICATE_REQUIRED => AlertDescription::CertificateRequired,
+            ffi::SSL_AD_NO_APPLICATION_PROTOCOL => AlertDescription::NoApplicationProtocol,
+            n => AlertDescription::Unknown(n),
+        }
+    }
+
+    /// Returns the wire value of the alert description.
+    pub fn to_u8(&self) -> u8 {
+        match *self {
+            AlertDescription::CloseNotify => ffi::SSL_AD_CLOSE_NOTIFY,
+            AlertDescription::UnexpectedMessage => ffi::SSL_AD_UNEXPECTED_MESSAGE,
+            AlertDescription::BadRecordMac => ffi::SSL_AD_BAD_RECORD_MAC,
+            AlertDescription::DecryptionFailed => ffi::SSL_AD_DECRYPTION_FAILED,
+            AlertDescription::RecordOverflow => ffi::SSL_AD_RECORD_OVERFLOW,
+            AlertDescription::DecompressionFailure => ffi::SSL_AD_DECOMPRESSION_FAILURE,
+            AlertDescription::HandshakeFailure => ffi::SSL_AD_HANDSHAKE_FAILURE,
+            AlertDescription::NoCertificate => ffi::SSL_AD_NO_CERTIFICATE,
+            AlertDescription::BadCertificate => ffi::SSL_AD_BAD_CERTIFICATE,
+            AlertDescription::UnsupportedCertificate => ffi::SSL_AD_UNSUPPORTED_CERTIFICATE,
+            AlertDescription::CertificateRevoked => ffi::SSL_AD_CERTIFICATE_REVOKED,
+            AlertDescription::CertificateExpired => ffi::SSL_AD_CERTIFICATE_EXPIRED,
+            AlertDescription::CertificateUnknown => ffi::SSL_AD_CERTIFICATE_UNKNOWN,
+            AlertDescription::IllegalParameter => ffi::SSL_AD_ILLEGAL_PARAMETER,
+            AlertDescription::UnknownCa => ffi::SSL_AD_UNKNOWN_CA,
+            AlertDescription::AccessDenied => ffi::SSL_AD_ACCESS_DENIED,
+            AlertDescription::DecodeError => ffi::SSL_AD_DECODE_ERROR,
+            AlertDescription::DecryptError => ffi::SSL_AD_DECRYPT_ERROR,
+            AlertDescription::ExportRestriction => ffi::SSL_AD_EXPORT_RESTRICTION,
+            AlertDescription::ProtocolVersion => ffi::SSL_AD_PROTOCOL_VERSION,
+            AlertDescription::InsufficientSecurity => ffi::SSL_AD_INSUFFICIENT_SECURITY,
+            AlertDescription::InternalError => ffi::SSL_AD_INTERNAL_ERROR,
+            AlertDescription::InappropriateFallback => ffi::SSL_AD_INAPPROPRIATE_FALLBACK,
+            AlertDescription::UserCancelled => ffi::SSL_AD_USER_CANCELLED,
+            AlertDescription::NoRenegotiation => ffi::SSL_AD_NO_RENEGOTIATION,
+            AlertDescription::MissingExtension => ffi::SSL_AD_MISSING_EXTENSION,
+            AlertDescription::UnsupportedExtension => ffi::SSL_AD_UNSUPPORTED_EXTENSION,
+            AlertDescription::CertificateUnobtainable => ffi::SSL_AD_CERTIFICATE_UNOBTAINABLE,
+            AlertDescription::UnrecognizedName => ffi::SSL_AD_UNRECOGNIZED_NAME,
+            AlertDescription::BadCertificateStatusResponse => {
+                ffi::SSL_AD_BAD_CERTIFICATE_STATUS_RESPONSE
+            }
+            AlertDescription::BadCertificateHashValue => ffi::SSL_AD_BAD_CERTIFICATE_HASH_VALUE,
+            AlertDescription::UnknownPskIdentity => ffi::SSL_AD_UNKNOWN_PSK_IDENTITY,
+            AlertDescription::CertificateRequired => ffi::SSL_AD_CERTIFICATE_REQUIRED,
+            AlertDescription::NoApplicationProtocol => ffi::SSL_AD_NO_APPLICATION_PROTOCOL,
+            AlertDescription::Unknown(n) => n,
+        }
+    }
+}
+
+impl fmt::Display for AlertDescription {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bs = unsafe {
+            let c_str = ffi::SSL_alert_desc_string_long(self.to_u8() as c_int);
+            CStr::from_ptr(c_str).to_bytes()
+        };
+        f.write_str(str::from_utf8(bs).unwrap())
+    }
+}
+
+/// A TLS alert observed on a connection.
+/// Alerts are recorded through the info callback as they are sent or received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alert {
+    /// The alert was sent by the peer.
+    Received(AlertLevel, AlertDescription),
+
+    /// The alert was sent by us.
+    Sent(AlertLevel, AlertDescription),
+}
+
+impl Alert {
+    /// Decodes the value passed to the info callback for SSL_CB_READ_ALERT and
+    /// SSL_CB_WRITE_ALERT, which is the alert level in the upper byte and the
+    /// description in the lower byte.
+    pub(crate) fn from_info(type_: c_int, value: c_int) -> Option<Alert> {
+        let level = AlertLevel::from_u8((value >> 8) as u8);
+        let desc = AlertDescription::from_u8(value as u8);
+        match type_ {
+            ffi::SSL_CB_READ_ALERT => Some(Alert::Received(level, desc)),
+            ffi::SSL_CB_WRITE_ALERT => Some(Alert::Sent(level, desc)),
+            _ => None,
+        }
+    }
+
+    pub fn level(&self) -> AlertLevel {
+        match *self {
+            Alert::Received(level, _) | Alert::Sent(level, _) => level,
+        }
+    }
+
+    pub fn description(&self) -> AlertDescription {
+        match *self {
+            Alert::Received(_, desc) | Alert::Sent(_, desc) => desc,
+        }
+    }
+
+    pub fn is_fatal(&self) -> bool {
+        self.level() == AlertLevel::Fatal
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (dir, level) = match *self {
+            Alert::Received(level, _) => ("received", level),
+            Alert::Sent(level, _) => ("sent", level),
+        };
+        let level = match level {
+            AlertLevel::Warning => "warning",
+            AlertLevel::Fatal => "fatal",
+            AlertLevel::Unknown(_) => "unknown",
+        };
+        write!(f, "{} {} alert: {}", dir, level, self.description())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_info() {
+        let value = (ffi::SSL3_AL_FATAL as c_int) << 8 | ffi::SSL_AD_HANDSHAKE_FAILURE as c_int;
+        let alert = Alert::from_info(ffi::SSL_CB_READ_ALERT, value).unwrap();
+        assert_eq!(alert,
+                   Alert::Received(AlertLevel::Fatal, AlertDescription::HandshakeFailure));
+        assert!(alert.is_fatal());
+
+        let value = (ffi::SSL3_AL_WARNING as c_int) << 8 | ffi::SSL_AD_CLOSE_NOTIFY as c_int;
+        let alert = Alert::from_info(ffi::SSL_CB_WRITE_ALERT, value).unwrap();
+        assert_eq!(alert, Alert::Sent(AlertLevel::Warning, AlertDescription::CloseNotify));
+        assert!(!alert.is_fatal());
+
+        // unknown levels and descriptions are kept
+        let alert = Alert::from_info(ffi::SSL_CB_READ_ALERT, 7 << 8 | 250).unwrap();
+        assert_eq!(alert.level(), AlertLevel::Unknown(7));
+        assert_eq!(alert.description(), AlertDescription::Unknown(250));
+
+        // other events are no alerts
+        assert_eq!(Alert::from_info(ffi::SSL_CB_HANDSHAKE_DONE, value), None);
+    }
+
+    #[test]
+    fn description_round_trip() {
+        for desc in 0..=255 {
+            assert_eq!(AlertDescription::from_u8(desc).to_u8(), desc);
+        }
+    }
+}
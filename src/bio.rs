@@ -0,0 +1,260 @@
+use std::io;
+use std::mem;
+use std::net::UdpSocket;
+use std::os::raw::{c_char, c_int, c_long, c_void};
+use std::os::unix::io::RawFd;
+use std::ptr;
+use std::slice;
+use std::sync::Once;
+
+use ffi;
+use {Error, Result, SslError};
+
+/// A BIO is the transport a connection reads from and writes to, e.g. a
+/// socket or an in-memory buffer.
+/// The underlying BIO is reference-counted: cloning is cheap and returns
+/// another handle to the same BIO, which is freed once the last handle is
+/// dropped and no connection uses it anymore.
+///
+/// A Bio is neither Send nor Sync, as BIOs are not thread-safe.
+pub struct Bio {
+    bio: *mut ffi::BIO,
+}
+
+impl Drop for Bio {
+    fn drop(&mut self) {
+        unsafe { ffi::BIO_free(self.bio) };
+    }
+}
+
+impl Clone for Bio {
+    fn clone(&self) -> Bio {
+        unsafe { ffi::BIO_up_ref(self.bio) };
+        Bio { bio: self.bio }
+    }
+}
+
+impl Bio {
+    fn from_ptr(bio: *mut ffi::BIO) -> Result<Bio> {
+        if bio.is_null() {
+            return Err(Error::AllocationFailed);
+        }
+        Ok(Bio { bio: bio })
+    }
+
+    /// Creates a BIO for the socket fd. The socket is not closed when the
+    /// BIO is freed.
+    pub fn new_socket(fd: RawFd) -> Result<Bio> {
+        Bio::from_ptr(unsafe { ffi::BIO_new_socket(fd, ffi::BIO_NOCLOSE) })
+    }
+
+    /// Creates an empty memory BIO. Reading returns what was written before;
+    /// when it is empty, reads fail with io::ErrorKind::WouldBlock.
+    pub fn new_mem() -> Result<Bio> {
+        Bio::from_ptr(unsafe { ffi::BIO_new(ffi::BIO_s_mem()) })
+    }
+
+    /// Creates a datagram BIO for DTLS over socket, which must be connected
+    /// to the peer. Each write sends one datagram and each read receives one.
+    /// If the socket is non-blocking, the connection fails with
+    /// Error::WantRead or Error::WantWrite when it would block.
+    pub fn new_udp(socket: UdpSocket) -> Result<Bio> {
+        let bio = try!(Bio::from_ptr(unsafe { ffi::BIO_new(udp_method()) }));
+        let socket = Box::into_raw(Box::new(socket));
+        unsafe {
+            ffi::BIO_set_data(bio.bio, socket as *mut c_void);
+            ffi::BIO_set_init(bio.bio, 1);
+        }
+        Ok(bio)
+    }
+
+    /// Creates a read-only memory BIO over buf, without copying it.
+    /// The BIO must not outlive buf.
+    pub(crate) fn from_slice(buf: &[u8]) -> Result<Bio> {
+        Bio::from_ptr(unsafe {
+            ffi::BIO_new_mem_buf(buf.as_ptr() as *const c_void, buf.len() as isize)
+        })
+    }
+
+    /// Creates two connected BIOs: data written to one can be read from the
+    /// other. Typically one end is attached to a connection and the other
+    /// carries its ciphertext to and from the network.
+    /// buf_size is the buffer size for each direction, or zero for the
+    /// default.
+    pub fn new_pair(buf_size: usize) -> Result<(Bio, Bio)> {
+        let mut bio1 = ptr::null_mut();
+        let mut bio2 = ptr::null_mut();
+        match unsafe { ffi::BIO_new_bio_pair(&mut bio1, buf_size, &mut bio2, buf_size) } {
+            1 => Ok((Bio { bio: bio1 }, Bio { bio: bio2 })),
+            _ => Err(Error::AllocationFailed),
+        }
+    }
+
+    /// Returns a copy of the contents of a memory BIO that have not been read
+    /// yet. It is copied as other handles may write to or read from the BIO.
+    pub fn contents(&self) -> Vec<u8> {
+        let mut contents = ptr::null();
+        let mut len = 0;
+        unsafe {
+            if ffi::BIO_mem_contents(self.bio, &mut contents, &mut len) != 1 || len == 0 {
+                return Vec::new();
+            }
+            slice::from_raw_parts(contents, len).to_vec()
+        }
+    }
+
+    /// Returns the number of bytes that can be read.
+    pub fn pending(&self) -> usize {
+        unsafe { ffi::BIO_pending(self.bio) }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut ffi::BIO {
+        self.bio
+    }
+
+    /// Gives up this handle's reference without freeing it, e.g. to pass its
+    /// ownership to an SSL.
+    pub(crate) fn into_ptr(self) -> *mut ffi::BIO {
+        let bio = self.bio;
+        mem::forget(self);
+        bio
+    }
+}
+
+/// Converts the result of BIO_read or BIO_write on bio.
+unsafe fn io_result(bio: *mut ffi::BIO, ret_code: c_int) -> io::Result<usize> {
+    if ret_code >= 0 {
+        return Ok(ret_code as usize);
+    }
+    // memory BIOs and BIO pairs signal empty or full buffers by asking to be
+    // retried
+    if ffi::BIO_should_retry(bio) != 0 {
+        return Err(io::Error::new(io::ErrorKind::WouldBlock, "BIO would block"));
+    }
+    match SslError::get() {
+        Some(err) => Err(io::Error::other(err)),
+        None => Err(io::Error::other("BIO operation failed")),
+    }
+}
+
+impl io::Read for Bio {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(c_int::MAX as usize) as c_int;
+        unsafe {
+            let ret_code = ffi::BIO_read(self.bio, buf.as_mut_ptr() as *mut c_void, len);
+            io_result(self.bio, ret_code)
+        }
+    }
+}
+
+impl io::Write for Bio {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(c_int::MAX as usize) as c_int;
+        unsafe {
+            let ret_code = ffi::BIO_write(self.bio, buf.as_ptr() as *const c_void, len);
+            io_result(self.bio, ret_code)
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Returns the BIO_METHOD of the datagram BIO created by Bio::new_udp.
+fn udp_method() -> *const ffi::BIO_METHOD {
+    static INIT: Once = Once::new();
+    static mut METHOD: *mut ffi::BIO_METHOD = ptr::null_mut();
+    unsafe {
+        INIT.call_once(|| {
+            let method = ffi::BIO_meth_new(ffi::BIO_TYPE_DGRAM,
+                                           b"UdpSocket\0".as_ptr() as *const c_char);
+            assert!(!method.is_null());
+            ffi::BIO_meth_set_write(method, Some(udp_write));
+            ffi::BIO_meth_set_read(method, Some(udp_read));
+            ffi::BIO_meth_set_ctrl(method, Some(udp_ctrl));
+            ffi::BIO_meth_set_destroy(method, Some(udp_destroy));
+            METHOD = method;
+        });
+        METHOD
+    }
+}
+
+unsafe fn udp_socket<'a>(bio: *mut ffi::BIO) -> &'a UdpSocket {
+    &*(ffi::BIO_get_data(bio) as *const UdpSocket)
+}
+
+/// Converts the result of a socket operation for a BIO, setting its retry
+/// flags if it would block.
+unsafe fn udp_result(bio: *mut ffi::BIO, result: io::Result<usize>, write: bool) -> c_int {
+    ffi::BIO_clear_retry_flags(bio);
+    match result {
+        Ok(len) => len as c_int,
+        Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+            if write {
+                ffi::BIO_set_retry_write(bio);
+            } else {
+                ffi::BIO_set_retry_read(bio);
+            }
+            -1
+        }
+        Err(_) => -1,
+    }
+}
+
+extern "C" fn udp_write(bio: *mut ffi::BIO, data: *const c_char, len: c_int) -> c_int {
+    unsafe {
+        let data = slice::from_raw_parts(data as *const u8, len as usize);
+        udp_result(bio, udp_socket(bio).send(data), true)
+    }
+}
+
+extern "C" fn udp_read(bio: *mut ffi::BIO, data: *mut c_char, len: c_int) -> c_int {
+    unsafe {
+        let data = slice::from_raw_parts_mut(data as *mut u8, len as usize);
+        udp_result(bio, udp_socket(bio).recv(data), false)
+    }
+}
+
+extern "C" fn udp_ctrl(_bio: *mut ffi::BIO,
+                       cmd: c_int,
+                       _larg: c_long,
+                       _parg: *mut c_void)
+                       -> c_long {
+    match cmd {
+        // datagrams are sent immediately
+        ffi::BIO_CTRL_FLUSH => 1,
+        _ => 0,
+    }
+}
+
+extern "C" fn udp_destroy(bio: *mut ffi::BIO) -> c_int {
+    unsafe {
+        let socket = ffi::BIO_get_data(bio) as *mut UdpSocket;
+        if !socket.is_null() {
+            drop(Box::from_raw(socket));
+            ffi::BIO_set_data(bio, ptr::null_mut());
+        }
+    }
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+
+    use super::*;
+
+    #[test]
+    fn io_errors() {
+        // an empty memory BIO asks to be retried
+        let mut bio = Bio::new_mem().unwrap();
+        let err = io::Read::read(&mut bio, &mut [0; 4]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        // writing to a read-only one fails
+        let mut bio = Bio::from_slice(b"read-only").unwrap();
+        let err = bio.write(b"hello").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+    }
+}
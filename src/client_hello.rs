@@ -0,0 +1,193 @@
+use std::os::raw::c_int;
+use std::ptr;
+use std::slice;
+use std::str;
+
+use ffi;
+use {Context, CtxState, Error, PrivateKey, Result, X509};
+
+pub type Callback = dyn Fn(&mut ClientHello) -> SelectCertificateResult + Send + Sync;
+
+/// The result of the select certificate callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectCertificateResult {
+    /// Continue the handshake.
+    Success,
+
+    /// Pause the handshake. The handshake fails with Error::PendingCertificate
+    /// and the callback is run again when it is retried, e.g. once a
+    /// certificate has been loaded asynchronously.
+    Retry,
+
+    /// Abort the handshake.
+    Error,
+}
+
+/// ClientHello is a parsed view of the ClientHello, passed to the select
+/// certificate callback before any other ClientHello processing.
+pub struct ClientHello<'a> {
+    hello: &'a ffi::SSL_CLIENT_HELLO,
+}
+
+impl<'a> ClientHello<'a> {
+    /// Returns the legacy_version field of the ClientHello. For TLS 1.3, the
+    /// offered versions are in the supported_versions extension.
+    pub fn version(&self) -> u16 {
+        self.hello.version
+    }
+
+    /// Returns the raw ClientHello message body.
+    pub fn raw(&self) -> &'a [u8] {
+        unsafe { to_slice(self.hello.client_hello, self.hello.client_hello_len) }
+    }
+
+    pub fn random(&self) -> &'a [u8] {
+        unsafe { to_slice(self.hello.random, self.hello.random_len) }
+    }
+
+    pub fn session_id(&self) -> &'a [u8] {
+        unsafe { to_slice(self.hello.session_id, self.hello.session_id_len) }
+    }
+
+    /// Returns the offered cipher suites, in the client's order of preference.
+    pub fn cipher_suites(&self) -> Vec<u16> {
+        let cs = unsafe { to_slice(self.hello.cipher_suites, self.hello.cipher_suites_len) };
+        cs.chunks(2)
+            .filter(|c| c.len() == 2)
+            .map(|c| (c[0] as u16) << 8 | c[1] as u16)
+            .collect()
+    }
+
+    /// Returns the type and contents of each extension, in the order sent.
+    pub fn extensions(&self) -> Vec<(u16, &'a [u8])> {
+        let mut exts = Vec::new();
+        let mut data = unsafe { to_slice(self.hello.extensions, self.hello.extensions_len) };
+        while data.len() >= 4 {
+            let type_ = (data[0] as u16) << 8 | data[1] as u16;
+            let len = (data[2] as usize) << 8 | data[3] as usize;
+            if data.len() < 4 + len {
+                break;
+            }
+            exts.push((type_, &data[4..4 + len]));
+            data = &data[4 + len..];
+        }
+        exts
+    }
+
+    /// Returns the contents of the extension of the given type, if present.
+    pub fn extension(&self, type_: u16) -> Option<&'a [u8]> {
+        let mut data = ptr::null();
+        let mut len = 0;
+        let found = unsafe {
+            ffi::SSL_early_callback_ctx_extension_get(self.hello, type_, &mut data, &mut len)
+        };
+        match found {
+            1 => Some(unsafe { to_slice(data, len) }),
+            _ => None,
+        }
+    }
+
+    /// Returns the host_name sent in the server_name extension, if any.
+    pub fn server_name(&self) -> Option<&'a str> {
+        // ServerNameList: u16 length, then entries of u8 name_type,
+        // u16 length and the name
+        let ext = self.extension(ffi::TLSEXT_TYPE_server_name);
+        let mut list = match ext.and_then(read_u16_prefixed) {
+            Some((list, _)) => list,
+            None => return None,
+        };
+        while list.len() >= 3 {
+            let name_type = list[0];
+            match read_u16_prefixed(&list[1..]) {
+                Some((name, _)) if name_type == ffi::TLSEXT_NAMETYPE_host_name as u8 => {
+                    return str::from_utf8(name).ok();
+                }
+                Some((_, rest)) => list = rest,
+                None => return None,
+            }
+        }
+        None
+    }
+
+    /// Returns the protocols offered in the ALPN extension, in the client's
+    /// order of preference.
+    pub fn alpn_protocols(&self) -> Vec<&'a [u8]> {
+        let mut protos = Vec::new();
+        let ext = match self.extension(ffi::TLSEXT_TYPE_application_layer_protocol_negotiation) {
+            Some(ext) => ext,
+            None => return protos,
+        };
+        // ProtocolNameList: u16 length, then entries of u8 length and the name
+        let mut list = match read_u16_prefixed(ext) {
+            Some((list, _)) => list,
+            None => return protos,
+        };
+        while !list.is_empty() {
+            let len = list[0] as usize;
+            if list.len() < 1 + len {
+                break;
+            }
+            protos.push(&list[1..1 + len]);
+            list = &list[1 + len..];
+        }
+        protos
+    }
+
+    /// Switches the connection to ctx, as with ServerNameRequest::set_context.
+    pub fn set_context(&mut self, ctx: &Context) -> Result<()> {
+        let ret = unsafe { ffi::SSL_set_SSL_CTX(self.hello.ssl, ctx.ctx) };
+        if ret.is_null() {
+            return Err(Error::Ssl(None));
+        }
+        Ok(())
+    }
+
+    /// Sets the leaf certificate for this connection only.
+    pub fn set_certificate(&mut self, cert: &X509) -> Result<()> {
+        match unsafe { ffi::SSL_use_certificate(self.hello.ssl, cert.as_ptr()) } {
+            1 => Ok(()),
+            _ => Err(Error::Ssl(None)),
+        }
+    }
+
+    /// Sets the private key for this connection only.
+    pub fn set_private_key(&mut self, key: &PrivateKey) -> Result<()> {
+        match unsafe { ffi::SSL_use_PrivateKey(self.hello.ssl, key.as_ptr()) } {
+            1 => Ok(()),
+            _ => Err(Error::Ssl(None)),
+        }
+    }
+}
+
+unsafe fn to_slice<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if data.is_null() || len == 0 {
+        return &[];
+    }
+    slice::from_raw_parts(data, len)
+}
+
+/// Splits a u16 length-prefixed vector off the front of data.
+fn read_u16_prefixed(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    if data.len() < 2 {
+        return None;
+    }
+    let len = (data[0] as usize) << 8 | data[1] as usize;
+    if data.len() < 2 + len {
+        return None;
+    }
+    Some((&data[2..2 + len], &data[2 + len..]))
+}
+
+pub extern "C" fn callback(hello: *const ffi::SSL_CLIENT_HELLO) -> c_int {
+    let hello = unsafe { &*hello };
+    let callback = match unsafe { ::ctx_state(hello.ssl) } {
+        Some(&CtxState { select_certificate_callback: Some(ref callback), .. }) => callback,
+        _ => return ffi::ssl_select_cert_success,
+    };
+    let mut hello = ClientHello { hello: hello };
+    match callback(&mut hello) {
+        SelectCertificateResult::Success => ffi::ssl_select_cert_success,
+        SelectCertificateResult::Retry => ffi::ssl_select_cert_retry,
+        SelectCertificateResult::Error => ffi::ssl_select_cert_error,
+    }
+}
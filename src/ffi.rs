@@ -1,6 +1,42 @@
-#![allow(non_camel_case_types)]
+#![allow(non_camel_case_types, non_upper_case_globals)]
 
-use std::os::raw::{c_void, c_char, c_int};
+use std::os::raw::{c_void, c_char, c_int, c_long, c_uint, c_ulong};
+
+/// Declares functions exported by BoringSSL. With the prefix-symbols feature,
+/// they are linked under the names BoringSSL gives them when it is built with
+/// BORINGSSL_PREFIX, e.g. PREFIX_SSL_new for SSL_new. Once gen-bindings.sh has
+/// generated sys.rs, each function must also be found there with the same
+/// signature, as compared by abi::Erase.
+macro_rules! boringssl {
+    ($($(#[$attr:meta])* pub fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)*;)*) => {
+        extern "C" {
+            $(
+                $(#[$attr])*
+                #[cfg_attr(feature = "prefix-symbols",
+                           link_name = concat!(env!("BORINGSSL_PREFIX"), "_", stringify!($name)))]
+                pub fn $name($($arg: $ty),*) $(-> $ret)*;
+            )*
+        }
+
+        #[cfg(boringssl_sys)]
+        const _: () = {
+            fn check() {
+                $(
+                    ::abi::same_signature(
+                        $name as unsafe extern "C" fn($($ty),*) $(-> $ret)*,
+                        ::sys::$name as unsafe extern "C" fn($(infer!($ty)),*) -> _);
+                )*
+            }
+        };
+    }
+}
+
+/// Expands to _ for each argument, to coerce a generated function to a
+/// pointer of the declared arity, but otherwise inferred types.
+#[cfg(boringssl_sys)]
+macro_rules! infer {
+    ($ty:ty) => { _ }
+}
 
 pub type SSL_METHOD = c_void;
 
@@ -17,10 +53,27 @@ pub type SSL_CTX = c_void;
 /// may only be used on one thread at a time.
 pub type SSL = c_void;
 
+/// An SSL_CIPHER represents a cipher suite. They are static and need not be
+/// freed.
+pub type SSL_CIPHER = c_void;
+
+/// An SSL_SESSION represents an SSL session that may be resumed in an
+/// abbreviated handshake. It is reference-counted and immutable; once
+/// established, an SSL_SESSION may be shared by multiple SSL objects on
+/// different threads.
+pub type SSL_SESSION = c_void;
+
+pub const SSL_SESS_CACHE_OFF: c_int = 0x0000;
+pub const SSL_SESS_CACHE_CLIENT: c_int = 0x0001;
+pub const SSL_SESS_CACHE_SERVER: c_int = 0x0002;
+pub const SSL_SESS_CACHE_BOTH: c_int = SSL_SESS_CACHE_CLIENT | SSL_SESS_CACHE_SERVER;
+
 pub const TLS1_VERSION: u16 = 0x0301;
 pub const TLS1_1_VERSION: u16 = 0x0302;
 pub const TLS1_2_VERSION: u16 = 0x0303;
 pub const TLS1_3_VERSION: u16 = 0x0304;
+pub const DTLS1_VERSION: u16 = 0xfeff;
+pub const DTLS1_2_VERSION: u16 = 0xfefd;
 
 // certificate verification modes
 pub const SSL_VERIFY_NONE: c_int = 0;
@@ -28,16 +81,22 @@ pub const SSL_VERIFY_PEER: c_int = 1;
 pub const SSL_VERIFY_FAIL_IF_NO_PEER_CERT: c_int = 2;
 pub const SSL_VERIFY_PEER_IF_NO_OBC: c_int = 4;
 
-extern "C" {
+boringssl! {
     /// TLS_method is the SSL_METHOD used for TLS (and SSLv3) connections.
     pub fn TLS_method() -> *const SSL_METHOD;
 
+    /// DTLS_method is the SSL_METHOD used for DTLS connections.
+    pub fn DTLS_method() -> *const SSL_METHOD;
+
     /// SSL_CTX_new returns a newly-allocated SSL_CTX with default settings or NULL on error.
     pub fn SSL_CTX_new(method: *const SSL_METHOD) -> *mut SSL_CTX;
 
     /// SSL_CTX_free releases memory associated with ctx.
     pub fn SSL_CTX_free(ctx: *mut SSL_CTX);
 
+    /// SSL_CTX_up_ref increments the reference count of ctx and returns one.
+    pub fn SSL_CTX_up_ref(ctx: *mut SSL_CTX) -> c_int;
+
     /// Configures the cipher list for ctx, evaluating str as a cipher string.
     /// It returns one on success and zero on failure.
     pub fn SSL_CTX_set_cipher_list(ssl: *mut SSL_CTX, list: *const c_char) -> c_int;
@@ -45,6 +104,9 @@ extern "C" {
     /// Sets the minimum protocol version for ctx to version.
     pub fn SSL_CTX_set_min_version(ssl: *mut SSL_CTX, version: u16);
 
+    /// Sets the maximum protocol version for ctx to version.
+    pub fn SSL_CTX_set_max_version(ssl: *mut SSL_CTX, version: u16);
+
     /// Configures certificate verification behavior.
     /// mode is one of the SSL_VERIFY_* values.
     /// callback, if not NULL, is used to customize certificate verification.
@@ -63,9 +125,113 @@ extern "C" {
     /// or configures a client to send TLS Channel IDs to a server.
     /// It returns 1.
     pub fn SSL_CTX_enable_tls_channel_id(ctx: *mut SSL_CTX) -> c_int;
+
+    /// SSL_CTX_get_cert_store returns ctx's certificate store, used to verify
+    /// peer certificates. The caller does not take ownership of the result.
+    pub fn SSL_CTX_get_cert_store(ctx: *const SSL_CTX) -> *mut X509_STORE;
+
+    /// SSL_CTX_use_certificate sets ctx's leaf certificate to x509.
+    /// It returns one on success and zero on failure.
+    pub fn SSL_CTX_use_certificate(ctx: *mut SSL_CTX, x509: *mut X509) -> c_int;
+
+    /// SSL_CTX_use_PrivateKey sets ctx's private key to pkey.
+    /// It returns one on success and zero on failure.
+    pub fn SSL_CTX_use_PrivateKey(ctx: *mut SSL_CTX, pkey: *mut EVP_PKEY) -> c_int;
+
+    /// SSL_CTX_check_private_key returns one if the certificate and private key
+    /// configured in ctx are consistent and zero otherwise.
+    pub fn SSL_CTX_check_private_key(ctx: *const SSL_CTX) -> c_int;
+
+    /// SSL_CTX_set_tlsext_servername_callback configures callback to be called
+    /// on the server after ClientHello extensions, in particular the
+    /// server_name extension, have been parsed. It returns one.
+    /// The callback returns one of the SSL_TLSEXT_ERR_* values. On
+    /// SSL_TLSEXT_ERR_ALERT_FATAL, the alert is taken from *out_alert.
+    pub fn SSL_CTX_set_tlsext_servername_callback(ctx: *mut SSL_CTX,
+                                                  callback: Option<extern "C" fn(*mut SSL,
+                                                                                 *mut c_int,
+                                                                                 *mut c_void)
+                                                                                 -> c_int>)
+                                                  -> c_int;
+
+    /// SSL_CTX_set_tlsext_servername_arg sets the argument to the servername
+    /// callback. It returns one.
+    pub fn SSL_CTX_set_tlsext_servername_arg(ctx: *mut SSL_CTX, arg: *mut c_void) -> c_int;
+
+    /// SSL_CTX_set_select_certificate_cb sets a callback that is called before
+    /// most ClientHello processing and before the decision whether to resume a
+    /// session is made. It may inspect the ClientHello and configure the
+    /// connection.
+    /// The callback returns one of the ssl_select_cert_* values. On
+    /// ssl_select_cert_retry, the handshake is paused and SSL_do_handshake
+    /// fails with SSL_ERROR_PENDING_CERTIFICATE.
+    pub fn SSL_CTX_set_select_certificate_cb(ctx: *mut SSL_CTX,
+                                             cb: Option<extern "C" fn(*const SSL_CLIENT_HELLO)
+                                                                      -> c_int>);
+
+    /// SSL_CTX_set_channel_id_cb sets a callback to be called when a TLS
+    /// Channel ID is requested and no key has been configured on the SSL.
+    /// The callback may set *out_pkey to a new reference to a P-256 key.
+    /// If it leaves *out_pkey NULL, the handshake fails with
+    /// SSL_ERROR_WANT_CHANNEL_ID_LOOKUP and the caller may configure a key
+    /// with SSL_set1_tls_channel_id before retrying.
+    pub fn SSL_CTX_set_channel_id_cb(ctx: *mut SSL_CTX,
+                                     cb: Option<extern "C" fn(*mut SSL, *mut *mut EVP_PKEY)>);
+
+    /// SSL_CTX_set_session_cache_mode sets the session cache mode bits for ctx
+    /// to mode, one of the SSL_SESS_CACHE_* values, and returns the previous
+    /// value. With SSL_SESS_CACHE_CLIENT, new client sessions are passed to
+    /// the new_session_cb.
+    pub fn SSL_CTX_set_session_cache_mode(ctx: *mut SSL_CTX, mode: c_int) -> c_int;
+
+    /// SSL_CTX_sess_set_new_cb sets the callback to be called when a new
+    /// session is established and ready to be cached. If the callback returns
+    /// one, it takes ownership of session; otherwise it must return zero.
+    pub fn SSL_CTX_sess_set_new_cb(ctx: *mut SSL_CTX,
+                                   new_session_cb: Option<extern "C" fn(ssl: *mut SSL,
+                                                                        session: *mut SSL_SESSION)
+                                                                        -> c_int>);
+
+    /// SSL_CTX_set_keylog_callback configures a callback to log key material.
+    /// This is intended for debugging use with tools like Wireshark. The cb
+    /// function is passed a line in the NSS key log format, without a
+    /// trailing newline.
+    pub fn SSL_CTX_set_keylog_callback(ctx: *mut SSL_CTX,
+                                       cb: Option<extern "C" fn(*const SSL, *const c_char)>);
+
+    /// SSL_CTX_set_msg_callback installs a callback which is called for every
+    /// protocol message sent or received. is_write is one for sent messages,
+    /// version is the protocol version, and content_type is the record type,
+    /// or SSL3_RT_HEADER for record headers. buf contains the message body.
+    pub fn SSL_CTX_set_msg_callback(ctx: *mut SSL_CTX,
+                                    cb: Option<extern "C" fn(c_int,
+                                                             c_int,
+                                                             c_int,
+                                                             *const c_void,
+                                                             usize,
+                                                             *mut SSL,
+                                                             *mut c_void)>);
+
+    /// SSL_CTX_get_ex_new_index allocates a new index for application-specific
+    /// data attached to SSL_CTX objects. It returns the index or a negative
+    /// number on error. free_func, if not NULL, is called when an SSL_CTX is
+    /// freed.
+    pub fn SSL_CTX_get_ex_new_index(argl: c_long,
+                                    argp: *mut c_void,
+                                    unused: *mut c_void,
+                                    dup_unused: *mut c_void,
+                                    free_func: Option<CRYPTO_EX_free>)
+                                    -> c_int;
+
+    /// SSL_CTX_set_ex_data sets the value at idx for ctx. It returns one on
+    /// success and zero on allocation error.
+    pub fn SSL_CTX_set_ex_data(ctx: *mut SSL_CTX, idx: c_int, data: *mut c_void) -> c_int;
+
+    /// SSL_CTX_get_ex_data returns the value at idx for ctx, or NULL if unset.
+    pub fn SSL_CTX_get_ex_data(ctx: *const SSL_CTX, idx: c_int) -> *mut c_void;
 }
 
-extern "C" {
+boringssl! {
     /// SSL_new returns a newly-allocated SSL using ctx or NULL on error.
     /// The new connection inherits settings from ctx at the time of creation.
     /// Settings may also be individually configured on the connection.
@@ -77,6 +243,181 @@ extern "C" {
     /// SSL_set_connect_state configures ssl as a client.
     pub fn SSL_set_connect_state(ssl: *mut SSL);
 
+    /// SSL_set_accept_state configures ssl as a server.
+    pub fn SSL_set_accept_state(ssl: *mut SSL);
+
+    /// SSL_get_servername, for a server, returns the hostname supplied by the
+    /// client in the server_name extension, or NULL if there was none.
+    /// name_type must be TLSEXT_NAMETYPE_host_name.
+    pub fn SSL_get_servername(ssl: *const SSL, name_type: c_int) -> *const c_char;
+
+    /// SSL_get_SSL_CTX returns the SSL_CTX associated with ssl.
+    pub fn SSL_get_SSL_CTX(ssl: *const SSL) -> *mut SSL_CTX;
+
+    /// SSL_use_certificate sets ssl's leaf certificate to x509.
+    /// It returns one on success and zero on failure.
+    pub fn SSL_use_certificate(ssl: *mut SSL, x509: *mut X509) -> c_int;
+
+    /// SSL_use_PrivateKey sets ssl's private key to pkey.
+    /// It returns one on success and zero on failure.
+    pub fn SSL_use_PrivateKey(ssl: *mut SSL, pkey: *mut EVP_PKEY) -> c_int;
+
+    /// SSL_early_callback_ctx_extension_get searches the extensions in
+    /// client_hello for an extension of the given type. If not found, it
+    /// returns zero. Otherwise it sets *out_data to point to the extension
+    /// contents (not including the type and length bytes), sets *out_len to
+    /// the length of the extension contents and returns one.
+    pub fn SSL_early_callback_ctx_extension_get(client_hello: *const SSL_CLIENT_HELLO,
+                                                extension_type: u16,
+                                                out_data: *mut *const u8,
+                                                out_len: *mut usize)
+                                                -> c_int;
+
+    /// SSL_set1_tls_channel_id configures a TLS client to send a TLS Channel
+    /// ID to compatible servers. private_key must be a P-256 EC key.
+    /// It returns one on success and zero on error.
+    pub fn SSL_set1_tls_channel_id(ssl: *mut SSL, private_key: *mut EVP_PKEY) -> c_int;
+
+    /// SSL_get_tls_channel_id gets the client's TLS Channel ID from a server
+    /// SSL and copies up to the first max_out bytes into out. The Channel ID
+    /// consists of the client's P-256 public key as an (x,y) pair where each
+    /// is a 32-byte, big-endian field element. It returns 0 if the client
+    /// didn't offer a Channel ID and the length of the complete Channel ID
+    /// otherwise.
+    pub fn SSL_get_tls_channel_id(ssl: *mut SSL, out: *mut u8, max_out: usize) -> usize;
+
+    /// SSL_export_keying_material exports a value derived from the master
+    /// secret, as specified in RFC 5705. It writes out_len bytes to out given
+    /// a label and optional context. If use_context is zero, the context is
+    /// omitted, which is distinct from an empty context in TLS 1.2.
+    /// It returns one on success and zero otherwise.
+    pub fn SSL_export_keying_material(ssl: *mut SSL,
+                                      out: *mut u8,
+                                      out_len: usize,
+                                      label: *const c_char,
+                                      label_len: usize,
+                                      context: *const u8,
+                                      context_len: usize,
+                                      use_context: c_int)
+                                      -> c_int;
+
+    /// SSL_get_tls_unique writes at most max_out bytes of the tls-unique value
+    /// for ssl to out and sets *out_len to the number of bytes written.
+    /// It returns one on success or zero on error, e.g. before the handshake
+    /// completes or in TLS 1.3, where tls-unique is not defined.
+    pub fn SSL_get_tls_unique(ssl: *const SSL,
+                              out: *mut u8,
+                              out_len: *mut usize,
+                              max_out: usize)
+                              -> c_int;
+
+    /// SSL_version returns the protocol version of ssl, e.g. TLS1_2_VERSION.
+    /// Before the version is negotiated, the result is unspecified.
+    pub fn SSL_version(ssl: *const SSL) -> c_int;
+
+    /// SSL_get_current_cipher returns the cipher used in the current outgoing
+    /// connection state, or NULL if the null cipher is active.
+    pub fn SSL_get_current_cipher(ssl: *const SSL) -> *const SSL_CIPHER;
+
+    /// SSL_CIPHER_standard_name returns the IETF name of cipher, e.g.
+    /// "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256".
+    pub fn SSL_CIPHER_standard_name(cipher: *const SSL_CIPHER) -> *const c_char;
+
+    /// SSL_get_curve_id returns the ID of the group used by ssl's most recently
+    /// completed handshake, or zero if not applicable.
+    pub fn SSL_get_curve_id(ssl: *const SSL) -> u16;
+
+    /// SSL_get_curve_name returns a human-readable name for the group with the
+    /// given ID, or NULL if it is unknown.
+    pub fn SSL_get_curve_name(curve_id: u16) -> *const c_char;
+
+    /// SSL_get_peer_signature_algorithm returns the signature algorithm used by
+    /// the peer. If not applicable, it returns zero.
+    pub fn SSL_get_peer_signature_algorithm(ssl: *const SSL) -> u16;
+
+    /// SSL_get_signature_algorithm_name returns a human-readable name for
+    /// sigalg, or NULL if it is unknown. If include_curve is one, the curve is
+    /// included for ECDSA algorithms which are ambiguous in TLS 1.2.
+    pub fn SSL_get_signature_algorithm_name(sigalg: u16, include_curve: c_int) -> *const c_char;
+
+    /// SSL_get0_alpn_selected sets *out_data and *out_len to the selected ALPN
+    /// protocol, or NULL and zero if none was negotiated.
+    pub fn SSL_get0_alpn_selected(ssl: *const SSL, out_data: *mut *const u8, out_len: *mut c_uint);
+
+    /// SSL_set_alpn_protos sets the client ALPN protocol list on ssl to protos,
+    /// in wire format: each protocol prefixed with its length in one byte.
+    /// It returns zero on success and one on failure.
+    pub fn SSL_set_alpn_protos(ssl: *mut SSL, protos: *const u8, protos_len: c_uint) -> c_int;
+
+    /// SSL_CTX_set_alpn_select_cb sets a callback function on ctx that is
+    /// called during ClientHello processing in order to select an ALPN
+    /// protocol from the client's list of offered protocols.
+    /// It returns SSL_TLSEXT_ERR_OK with *out and *out_len set to a protocol
+    /// from in, or SSL_TLSEXT_ERR_NOACK to select none.
+    pub fn SSL_CTX_set_alpn_select_cb(ctx: *mut SSL_CTX,
+                                      cb: Option<extern "C" fn(ssl: *mut SSL,
+                                                               out: *mut *const u8,
+                                                               out_len: *mut u8,
+                                                               in_: *const u8,
+                                                               in_len: c_uint,
+                                                               arg: *mut c_void)
+                                                               -> c_int>,
+                                      arg: *mut c_void);
+
+    /// SSL_select_next_proto selects the first protocol in peer which is also
+    /// in supported, both in wire format, and points *out to it.
+    /// It returns OPENSSL_NPN_NEGOTIATED on success and OPENSSL_NPN_NO_OVERLAP
+    /// if there is none.
+    pub fn SSL_select_next_proto(out: *mut *mut u8,
+                                 out_len: *mut u8,
+                                 peer: *const u8,
+                                 peer_len: c_uint,
+                                 supported: *const u8,
+                                 supported_len: c_uint)
+                                 -> c_int;
+
+    /// SSL_CIPHER_get_protocol_id returns cipher's two-byte protocol ID.
+    pub fn SSL_CIPHER_get_protocol_id(cipher: *const SSL_CIPHER) -> u16;
+
+    /// SSL_session_reused returns one if ssl performed an abbreviated
+    /// handshake and zero otherwise.
+    pub fn SSL_session_reused(ssl: *const SSL) -> c_int;
+
+    /// SSL_set_session, for a client, configures ssl to offer to resume
+    /// session in the initial handshake and returns one. The caller retains
+    /// ownership of session.
+    pub fn SSL_set_session(ssl: *mut SSL, session: *mut SSL_SESSION) -> c_int;
+
+    /// SSL_get1_session acts like SSL_get_session, returning ssl's established
+    /// session, or NULL, but returns a new reference to the session.
+    /// In TLS 1.3, the established session is not resumable: sessions are
+    /// received after the handshake and passed to the new_session_cb.
+    pub fn SSL_get1_session(ssl: *mut SSL) -> *mut SSL_SESSION;
+
+    /// SSL_SESSION_up_ref increments the reference count of session and
+    /// returns one.
+    pub fn SSL_SESSION_up_ref(session: *mut SSL_SESSION) -> c_int;
+
+    /// SSL_SESSION_free decrements the reference count of session. If it
+    /// reaches zero, all data referenced by session and session itself are
+    /// released.
+    pub fn SSL_SESSION_free(session: *mut SSL_SESSION);
+
+    /// SSL_early_data_accepted returns whether early data was accepted on the
+    /// handshake performed by ssl.
+    pub fn SSL_early_data_accepted(ssl: *const SSL) -> c_int;
+
+    /// SSL_in_false_start returns one if ssl has a pending handshake that is in
+    /// False Start. SSL_write may be called at this point without waiting for
+    /// the peer, but SSL_read will complete the handshake before accepting
+    /// application data.
+    pub fn SSL_in_false_start(ssl: *const SSL) -> c_int;
+
+    /// SSL_set_SSL_CTX changes ssl's SSL_CTX. ssl will use the certificate
+    /// and key of ctx for the remainder of the handshake.
+    /// It returns ctx on success and NULL on failure.
+    pub fn SSL_set_SSL_CTX(ssl: *mut SSL, ctx: *mut SSL_CTX) -> *mut SSL_CTX;
+
     /// SSL_do_handshake starts or continues the current handshake.
     /// If there is none or the handshake has completed or False Started, it
     /// returns one. Otherwise, it returns <= 0. The caller should pass the
@@ -100,6 +441,23 @@ extern "C" {
     /// On Windows, fd is cast to a SOCKET and used with Winsock APIs.
     pub fn SSL_set_fd(ssl: *mut SSL, fd: c_int) -> c_int;
 
+    /// SSL_set_mtu sets the ssl's MTU in DTLS to mtu. It returns one on success
+    /// and zero on error, e.g. if mtu is too small.
+    pub fn SSL_set_mtu(ssl: *mut SSL, mtu: c_uint) -> c_int;
+
+    /// DTLSv1_get_timeout queries the next DTLS handshake timeout. If there is
+    /// a timeout in progress, it sets *out to the time remaining and returns
+    /// one. Otherwise, it returns zero.
+    /// When the timeout expires, the caller must call DTLSv1_handle_timeout.
+    pub fn DTLSv1_get_timeout(ssl: *const SSL, out: *mut timeval) -> c_int;
+
+    /// DTLSv1_handle_timeout is called when a DTLS handshake timeout expires.
+    /// If no timeout had expired, it returns zero. Otherwise, it retransmits
+    /// the previous flight of handshake messages and returns one. If too many
+    /// timeouts had expired without progress or an error occurs, it returns
+    /// -1.
+    pub fn DTLSv1_handle_timeout(ssl: *mut SSL) -> c_int;
+
     /// SSL_read reads up to num bytes from ssl into buf.
     /// It implicitly runs any pending handshakes, including renegotiations when
     /// enabled. On success, it returns the number of bytes read. Otherwise, it
@@ -144,6 +502,347 @@ extern "C" {
     /// on ssl. It should be called after an operation failed to determine
     /// whether the error was fatal and, if not, when to retry.
     pub fn SSL_get_error(ssl: *const SSL, ret_code: c_int) -> c_int;
+
+    /// SSL_get_peer_certificate returns the peer's leaf certificate or NULL if
+    /// the peer did not use certificate authentication.
+    /// The caller must release the result with X509_free.
+    pub fn SSL_get_peer_certificate(ssl: *const SSL) -> *mut X509;
+
+    /// SSL_get_peer_cert_chain returns the peer's certificate chain or NULL if
+    /// unavailable. For a client, the chain includes the leaf certificate.
+    /// The caller does not take ownership of the result.
+    pub fn SSL_get_peer_cert_chain(ssl: *const SSL) -> *mut _STACK;
+
+    /// SSL_get_verify_result returns the result of certificate verification,
+    /// one of the X509_V_* values.
+    pub fn SSL_get_verify_result(ssl: *const SSL) -> c_long;
+
+    /// SSL_get_verify_mode returns ssl's verification mode, one of the
+    /// SSL_VERIFY_* values.
+    pub fn SSL_get_verify_mode(ssl: *const SSL) -> c_int;
+
+    /// SSL_set_info_callback configures a callback to be run when various
+    /// events occur during a connection's lifetime. The type argument
+    /// determines the type of event and the meaning of the value argument.
+    /// A callback set on ssl takes precedence over one set on its SSL_CTX.
+    pub fn SSL_set_info_callback(ssl: *mut SSL,
+                                 callback: Option<extern "C" fn(*const SSL, c_int, c_int)>);
+
+    /// SSL_state_string_long returns a string describing the current
+    /// handshake state of ssl.
+    pub fn SSL_state_string_long(ssl: *const SSL) -> *const c_char;
+
+    /// SSL_alert_desc_string_long returns a string description of the alert
+    /// description in value.
+    pub fn SSL_alert_desc_string_long(value: c_int) -> *const c_char;
+}
+
+/// SSL_CLIENT_HELLO is a parsed ClientHello, passed to the select
+/// certificate callback. The pointers are only valid during the callback.
+#[repr(C)]
+pub struct SSL_CLIENT_HELLO {
+    pub ssl: *mut SSL,
+    pub client_hello: *const u8,
+    pub client_hello_len: usize,
+    pub version: u16,
+    pub random: *const u8,
+    pub random_len: usize,
+    pub session_id: *const u8,
+    pub session_id_len: usize,
+    pub cipher_suites: *const u8,
+    pub cipher_suites_len: usize,
+    pub compression_methods: *const u8,
+    pub compression_methods_len: usize,
+    pub extensions: *const u8,
+    pub extensions_len: usize,
+}
+
+// select certificate callback return values
+pub const ssl_select_cert_success: c_int = 1;
+pub const ssl_select_cert_retry: c_int = 0;
+pub const ssl_select_cert_error: c_int = -1;
+
+pub const TLSEXT_TYPE_server_name: u16 = 0;
+pub const TLSEXT_TYPE_application_layer_protocol_negotiation: u16 = 16;
+
+// private key operation results
+#[cfg(boringssl_private_key_method)]
+pub const ssl_private_key_success: c_int = 0;
+#[cfg(boringssl_private_key_method)]
+pub const ssl_private_key_retry: c_int = 1;
+#[cfg(boringssl_private_key_method)]
+pub const ssl_private_key_failure: c_int = 2;
+
+/// SSL_PRIVATE_KEY_METHOD describes private key hooks, used to offload
+/// signing and decryption operations, e.g. to a hardware module.
+/// Each operation may return ssl_private_key_retry, in which case the
+/// handshake fails with SSL_ERROR_WANT_PRIVATE_KEY_OPERATION and complete is
+/// called when it is retried.
+/// Older BoringSSL has more hooks in a different layout, so build.rs only
+/// sets boringssl_private_key_method if ssl.h declares this one.
+#[cfg(boringssl_private_key_method)]
+#[repr(C)]
+pub struct SSL_PRIVATE_KEY_METHOD {
+    /// sign signs in_len bytes at in with the signature algorithm
+    /// signature_algorithm, writing at most max_out bytes to out and the length
+    /// to *out_len.
+    pub sign: Option<extern "C" fn(ssl: *mut SSL,
+                                   out: *mut u8,
+                                   out_len: *mut usize,
+                                   max_out: usize,
+                                   signature_algorithm: u16,
+                                   in_: *const u8,
+                                   in_len: usize)
+                                   -> c_int>,
+
+    /// decrypt decrypts in_len bytes at in with an RSA private key, writing at
+    /// most max_out bytes to out and the length to *out_len.
+    pub decrypt: Option<extern "C" fn(ssl: *mut SSL,
+                                      out: *mut u8,
+                                      out_len: *mut usize,
+                                      max_out: usize,
+                                      in_: *const u8,
+                                      in_len: usize)
+                                      -> c_int>,
+
+    /// complete completes a pending sign or decrypt operation, writing at most
+    /// max_out bytes to out and the length to *out_len.
+    pub complete: Option<extern "C" fn(ssl: *mut SSL,
+                                       out: *mut u8,
+                                       out_len: *mut usize,
+                                       max_out: usize)
+                                       -> c_int>,
+}
+
+#[cfg(boringssl_private_key_method)]
+boringssl! {
+    /// SSL_CTX_set_private_key_method configures a custom private key on ctx.
+    /// key_method must remain valid for the lifetime of ctx.
+    pub fn SSL_CTX_set_private_key_method(ctx: *mut SSL_CTX,
+                                          key_method: *const SSL_PRIVATE_KEY_METHOD);
+
+    /// SSL_set_private_key_method configures a custom private key on ssl.
+    /// key_method must remain valid for the lifetime of ssl.
+    pub fn SSL_set_private_key_method(ssl: *mut SSL, key_method: *const SSL_PRIVATE_KEY_METHOD);
+
+    /// SSL_get_signature_algorithm_digest returns the digest function
+    /// associated with sigalg or NULL if sigalg has no prehash (Ed25519) or
+    /// is unknown.
+    pub fn SSL_get_signature_algorithm_digest(sigalg: u16) -> *const EVP_MD;
+
+    /// SSL_is_signature_algorithm_rsa_pss returns one if sigalg is an RSA-PSS
+    /// signature algorithm and zero otherwise.
+    pub fn SSL_is_signature_algorithm_rsa_pss(sigalg: u16) -> c_int;
+
+    /// EVP_DigestSign signs in_len bytes at data. If out_sig is NULL, it sets
+    /// *out_sig_len to the maximum signature length. Otherwise *out_sig_len
+    /// must contain the number of bytes of space at out_sig and is set to the
+    /// length of the signature. It returns one on success and zero on error.
+    pub fn EVP_DigestSign(ctx: *mut EVP_MD_CTX,
+                          out_sig: *mut u8,
+                          out_sig_len: *mut usize,
+                          data: *const u8,
+                          data_len: usize)
+                          -> c_int;
+}
+
+pub const OPENSSL_NPN_NEGOTIATED: c_int = 1;
+pub const OPENSSL_NPN_NO_OVERLAP: c_int = 2;
+
+/// ssl_encryption_level_t represents an encryption level in QUIC.
+#[cfg(feature = "quic")]
+pub type ssl_encryption_level_t = c_int;
+#[cfg(feature = "quic")]
+pub const ssl_encryption_initial: ssl_encryption_level_t = 0;
+#[cfg(feature = "quic")]
+pub const ssl_encryption_early_data: ssl_encryption_level_t = 1;
+#[cfg(feature = "quic")]
+pub const ssl_encryption_handshake: ssl_encryption_level_t = 2;
+#[cfg(feature = "quic")]
+pub const ssl_encryption_application: ssl_encryption_level_t = 3;
+
+/// SSL_QUIC_METHOD describes the hooks used by a QUIC transport to drive the
+/// TLS 1.3 handshake. Instead of records, handshake data is passed to
+/// add_handshake_data and received with SSL_provide_quic_data, and the
+/// traffic secrets are passed to the transport. Each callback returns one on
+/// success and zero on error, which aborts the handshake.
+#[cfg(feature = "quic")]
+#[repr(C)]
+pub struct SSL_QUIC_METHOD {
+    /// set_read_secret configures the read secret and cipher suite for level.
+    pub set_read_secret: Option<extern "C" fn(ssl: *mut SSL,
+                                              level: ssl_encryption_level_t,
+                                              cipher: *const SSL_CIPHER,
+                                              secret: *const u8,
+                                              secret_len: usize)
+                                              -> c_int>,
+
+    /// set_write_secret configures the write secret and cipher suite for
+    /// level.
+    pub set_write_secret: Option<extern "C" fn(ssl: *mut SSL,
+                                               level: ssl_encryption_level_t,
+                                               cipher: *const SSL_CIPHER,
+                                               secret: *const u8,
+                                               secret_len: usize)
+                                               -> c_int>,
+
+    /// add_handshake_data adds handshake data to the current flight at level.
+    pub add_handshake_data: Option<extern "C" fn(ssl: *mut SSL,
+                                                 level: ssl_encryption_level_t,
+                                                 data: *const u8,
+                                                 len: usize)
+                                                 -> c_int>,
+
+    /// flush_flight is called when the current flight is complete and should
+    /// be written to the transport.
+    pub flush_flight: Option<extern "C" fn(ssl: *mut SSL) -> c_int>,
+
+    /// send_alert sends a fatal alert at level.
+    pub send_alert: Option<extern "C" fn(ssl: *mut SSL,
+                                         level: ssl_encryption_level_t,
+                                         alert: u8)
+                                         -> c_int>,
+}
+
+#[cfg(feature = "quic")]
+boringssl! {
+    /// SSL_set_quic_method configures the QUIC hooks on ssl, which must be
+    /// configured for TLS 1.3 only. quic_method must remain valid for the
+    /// lifetime of ssl. It returns one on success and zero on error.
+    pub fn SSL_set_quic_method(ssl: *mut SSL, quic_method: *const SSL_QUIC_METHOD) -> c_int;
+
+    /// SSL_provide_quic_data provides len bytes of handshake data received at
+    /// level to ssl. It returns one on success and zero on error, e.g. if the
+    /// data was received at the wrong level.
+    /// The data is processed by the next call to SSL_do_handshake or
+    /// SSL_process_quic_post_handshake.
+    pub fn SSL_provide_quic_data(ssl: *mut SSL,
+                                 level: ssl_encryption_level_t,
+                                 data: *const u8,
+                                 len: usize)
+                                 -> c_int;
+
+    /// SSL_process_quic_post_handshake processes any data that QUIC has
+    /// provided after the handshake has completed, e.g. NewSessionTicket
+    /// messages. It returns one on success and zero on error.
+    pub fn SSL_process_quic_post_handshake(ssl: *mut SSL) -> c_int;
+
+    /// SSL_quic_read_level returns the encryption level at which ssl
+    /// currently expects to read handshake data.
+    pub fn SSL_quic_read_level(ssl: *const SSL) -> ssl_encryption_level_t;
+
+    /// SSL_quic_write_level returns the encryption level at which ssl
+    /// currently writes handshake data.
+    pub fn SSL_quic_write_level(ssl: *const SSL) -> ssl_encryption_level_t;
+
+    /// SSL_set_quic_transport_params configures ssl to send params in the
+    /// quic_transport_parameters extension. It returns one on success and zero
+    /// on error.
+    pub fn SSL_set_quic_transport_params(ssl: *mut SSL,
+                                         params: *const u8,
+                                         params_len: usize)
+                                         -> c_int;
+
+    /// SSL_get_peer_quic_transport_params sets *out_params and *out_params_len
+    /// to the quic_transport_parameters sent by the peer, or NULL and zero if
+    /// it sent none.
+    pub fn SSL_get_peer_quic_transport_params(ssl: *const SSL,
+                                              out_params: *mut *const u8,
+                                              out_params_len: *mut usize);
+}
+
+// servername callback return values
+pub const SSL_TLSEXT_ERR_OK: c_int = 0;
+pub const SSL_TLSEXT_ERR_ALERT_WARNING: c_int = 1;
+pub const SSL_TLSEXT_ERR_ALERT_FATAL: c_int = 2;
+pub const SSL_TLSEXT_ERR_NOACK: c_int = 3;
+
+pub const TLSEXT_NAMETYPE_host_name: c_int = 0;
+
+// info callback event types
+pub const SSL_CB_LOOP: c_int = 0x01;
+pub const SSL_CB_EXIT: c_int = 0x02;
+pub const SSL_CB_READ: c_int = 0x04;
+pub const SSL_CB_WRITE: c_int = 0x08;
+pub const SSL_CB_ALERT: c_int = 0x4000;
+pub const SSL_CB_READ_ALERT: c_int = SSL_CB_ALERT | SSL_CB_READ;
+pub const SSL_CB_WRITE_ALERT: c_int = SSL_CB_ALERT | SSL_CB_WRITE;
+pub const SSL_CB_HANDSHAKE_START: c_int = 0x10;
+pub const SSL_CB_HANDSHAKE_DONE: c_int = 0x20;
+
+// record content types, as passed to the msg callback
+pub const SSL3_RT_CHANGE_CIPHER_SPEC: c_int = 20;
+pub const SSL3_RT_ALERT: c_int = 21;
+pub const SSL3_RT_HANDSHAKE: c_int = 22;
+pub const SSL3_RT_APPLICATION_DATA: c_int = 23;
+pub const SSL3_RT_HEADER: c_int = 0x100;
+
+// alert levels
+pub const SSL3_AL_WARNING: u8 = 1;
+pub const SSL3_AL_FATAL: u8 = 2;
+
+// alert descriptions
+pub const SSL_AD_CLOSE_NOTIFY: u8 = 0;
+pub const SSL_AD_UNEXPECTED_MESSAGE: u8 = 10;
+pub const SSL_AD_BAD_RECORD_MAC: u8 = 20;
+pub const SSL_AD_DECRYPTION_FAILED: u8 = 21;
+pub const SSL_AD_RECORD_OVERFLOW: u8 = 22;
+pub const SSL_AD_DECOMPRESSION_FAILURE: u8 = 30;
+pub const SSL_AD_HANDSHAKE_FAILURE: u8 = 40;
+pub const SSL_AD_NO_CERTIFICATE: u8 = 41;
+pub const SSL_AD_BAD_CERTIFICATE: u8 = 42;
+pub const SSL_AD_UNSUPPORTED_CERTIFICATE: u8 = 43;
+pub const SSL_AD_CERTIFICATE_REVOKED: u8 = 44;
+pub const SSL_AD_CERTIFICATE_EXPIRED: u8 = 45;
+pub const SSL_AD_CERTIFICATE_UNKNOWN: u8 = 46;
+pub const SSL_AD_ILLEGAL_PARAMETER: u8 = 47;
+pub const SSL_AD_UNKNOWN_CA: u8 = 48;
+pub const SSL_AD_ACCESS_DENIED: u8 = 49;
+pub const SSL_AD_DECODE_ERROR: u8 = 50;
+pub const SSL_AD_DECRYPT_ERROR: u8 = 51;
+pub const SSL_AD_EXPORT_RESTRICTION: u8 = 60;
+pub const SSL_AD_PROTOCOL_VERSION: u8 = 70;
+pub const SSL_AD_INSUFFICIENT_SECURITY: u8 = 71;
+pub const SSL_AD_INTERNAL_ERROR: u8 = 80;
+pub const SSL_AD_INAPPROPRIATE_FALLBACK: u8 = 86;
+pub const SSL_AD_USER_CANCELLED: u8 = 90;
+pub const SSL_AD_NO_RENEGOTIATION: u8 = 100;
+pub const SSL_AD_MISSING_EXTENSION: u8 = 109;
+pub const SSL_AD_UNSUPPORTED_EXTENSION: u8 = 110;
+pub const SSL_AD_CERTIFICATE_UNOBTAINABLE: u8 = 111;
+pub const SSL_AD_UNRECOGNIZED_NAME: u8 = 112;
+pub const SSL_AD_BAD_CERTIFICATE_STATUS_RESPONSE: u8 = 113;
+pub const SSL_AD_BAD_CERTIFICATE_HASH_VALUE: u8 = 114;
+pub const SSL_AD_UNKNOWN_PSK_IDENTITY: u8 = 115;
+pub const SSL_AD_CERTIFICATE_REQUIRED: u8 = 116;
+pub const SSL_AD_NO_APPLICATION_PROTOCOL: u8 = 120;
+
+/// CRYPTO_EX_free is called when an object carrying ex_data is freed.
+/// ptr is the value stored at index for that object, and may be NULL.
+pub type CRYPTO_EX_free = extern "C" fn(parent: *mut c_void,
+                                        ptr: *mut c_void,
+                                        ad: *mut c_void,
+                                        index: c_int,
+                                        argl: c_long,
+                                        argp: *mut c_void);
+
+boringssl! {
+    /// SSL_get_ex_new_index allocates a new index for application-specific
+    /// data attached to SSL objects. It returns the index or a negative number
+    /// on error. free_func, if not NULL, is called when an SSL is freed.
+    pub fn SSL_get_ex_new_index(argl: c_long,
+                                argp: *mut c_void,
+                                unused: *mut c_void,
+                                dup_unused: *mut c_void,
+                                free_func: Option<CRYPTO_EX_free>)
+                                -> c_int;
+
+    /// SSL_set_ex_data sets the value at idx for ssl. It returns one on
+    /// success and zero on allocation error.
+    pub fn SSL_set_ex_data(ssl: *mut SSL, idx: c_int, data: *mut c_void) -> c_int;
+
+    /// SSL_get_ex_data returns the value at idx for ssl, or NULL if unset.
+    pub fn SSL_get_ex_data(ssl: *const SSL, idx: c_int) -> *mut c_void;
 }
 
 pub const BIO_NOCLOSE: c_int = 0;
@@ -152,12 +851,361 @@ pub const BIO_CLOSE: c_int = 1;
 pub type BIO_METHOD = c_void;
 pub type BIO = c_void;
 
-extern "C" {
+pub const BIO_TYPE_DESCRIPTOR: c_int = 0x0100;
+pub const BIO_TYPE_SOURCE_SINK: c_int = 0x0400;
+pub const BIO_TYPE_DGRAM: c_int = 21 | BIO_TYPE_SOURCE_SINK | BIO_TYPE_DESCRIPTOR;
+
+pub const BIO_CTRL_FLUSH: c_int = 11;
+
+boringssl! {
     pub fn BIO_new(method: *const BIO_METHOD) -> *mut BIO;
     pub fn BIO_free(bio: *mut BIO) -> c_int;
+
+    /// BIO_up_ref increments the reference count of bio and returns one.
+    pub fn BIO_up_ref(bio: *mut BIO) -> c_int;
+
     pub fn BIO_read(bio: *mut BIO, data: *mut c_void, len: c_int) -> c_int;
     pub fn BIO_write(bio: *mut BIO, data: *const c_void, len: c_int) -> c_int;
     pub fn BIO_new_socket(fd: c_int, close_flag: c_int) -> *mut BIO;
+    pub fn BIO_s_mem() -> *const BIO_METHOD;
+
+    /// BIO_new_mem_buf creates a read-only BIO that reads from len bytes at buf.
+    /// The data must remain valid for the lifetime of the BIO.
+    pub fn BIO_new_mem_buf(buf: *const c_void, len: isize) -> *mut BIO;
+
+    /// BIO_mem_contents sets *out_contents to point to the current contents of
+    /// a memory BIO and *out_len to the length. It returns one on success.
+    pub fn BIO_mem_contents(bio: *const BIO,
+                            out_contents: *mut *const u8,
+                            out_len: *mut usize)
+                            -> c_int;
+
+    /// BIO_meth_new returns a newly-allocated BIO_METHOD or NULL on allocation
+    /// error. The callbacks are set with the BIO_meth_set_* functions.
+    pub fn BIO_meth_new(type_: c_int, name: *const c_char) -> *mut BIO_METHOD;
+
+    pub fn BIO_meth_set_write(method: *mut BIO_METHOD,
+                              write: Option<extern "C" fn(bio: *mut BIO,
+                                                          data: *const c_char,
+                                                          len: c_int)
+                                                          -> c_int>)
+                              -> c_int;
+
+    pub fn BIO_meth_set_read(method: *mut BIO_METHOD,
+                             read: Option<extern "C" fn(bio: *mut BIO,
+                                                        data: *mut c_char,
+                                                        len: c_int)
+                                                        -> c_int>)
+                             -> c_int;
+
+    pub fn BIO_meth_set_ctrl(method: *mut BIO_METHOD,
+                             ctrl: Option<extern "C" fn(bio: *mut BIO,
+                                                        cmd: c_int,
+                                                        larg: c_long,
+                                                        parg: *mut c_void)
+                                                        -> c_long>)
+                             -> c_int;
+
+    /// BIO_meth_set_destroy sets the function called when a BIO of this method
+    /// is freed. It must release the data set with BIO_set_data.
+    pub fn BIO_meth_set_destroy(method: *mut BIO_METHOD,
+                                destroy: Option<extern "C" fn(bio: *mut BIO) -> c_int>)
+                                -> c_int;
+
+    pub fn BIO_set_data(bio: *mut BIO, ptr: *mut c_void);
+    pub fn BIO_get_data(bio: *mut BIO) -> *mut c_void;
+    pub fn BIO_set_init(bio: *mut BIO, init: c_int);
+
+    /// BIO_set_retry_read and BIO_set_retry_write mark bio as needing to be
+    /// retried once it is readable or writable, respectively.
+    pub fn BIO_set_retry_read(bio: *mut BIO);
+    pub fn BIO_set_retry_write(bio: *mut BIO);
+    pub fn BIO_clear_retry_flags(bio: *mut BIO);
+
+    /// BIO_should_retry returns non-zero if the last operation on bio failed
+    /// only because it should be retried later, e.g. as a buffer was empty.
+    pub fn BIO_should_retry(bio: *const BIO) -> c_int;
+
+    /// BIO_pending returns the number of bytes pending to be read.
+    pub fn BIO_pending(bio: *const BIO) -> usize;
+
+    /// BIO_new_bio_pair sets *out1 and *out2 to two freshly created BIOs where
+    /// data written to one can be read from the other. writebuf1 and writebuf2
+    /// are the buffer sizes for each direction, or zero for the default.
+    /// It returns one on success and zero on error.
+    pub fn BIO_new_bio_pair(out1: *mut *mut BIO,
+                            writebuf1: usize,
+                            out2: *mut *mut BIO,
+                            writebuf2: usize)
+                            -> c_int;
+}
+
+/// X509 is a parsed, reference-counted X.509 certificate.
+pub type X509 = c_void;
+pub type X509_NAME = c_void;
+pub type ASN1_STRING = c_void;
+pub type ASN1_INTEGER = ASN1_STRING;
+pub type ASN1_BIT_STRING = ASN1_STRING;
+pub type ASN1_OBJECT = c_void;
+pub type EVP_PKEY = c_void;
+pub type EVP_MD = c_void;
+pub type EVP_MD_CTX = c_void;
+pub type EVP_PKEY_CTX = c_void;
+
+pub const RSA_PKCS1_PSS_PADDING: c_int = 6;
+
+#[cfg(unix)]
+pub type time_t = c_long;
+
+#[repr(C)]
+pub struct timeval {
+    pub tv_sec: time_t,
+    pub tv_usec: c_long,
+}
+
+/// X509_CRL is a parsed, reference-counted certificate revocation list.
+pub type X509_CRL = c_void;
+
+/// X509_STORE holds the trust anchors and CRLs used for certificate
+/// verification.
+pub type X509_STORE = c_void;
+
+/// X509_VERIFY_PARAM holds the flags and limits of certificate verification.
+pub type X509_VERIFY_PARAM = c_void;
+
+// certificate verification flags
+pub const X509_V_FLAG_CRL_CHECK: c_ulong = 0x4;
+pub const X509_V_FLAG_CRL_CHECK_ALL: c_ulong = 0x8;
+
+// certificate verification results
+pub const X509_V_OK: c_long = 0;
+pub const X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT: c_long = 2;
+pub const X509_V_ERR_UNABLE_TO_GET_CRL: c_long = 3;
+pub const X509_V_ERR_CERT_SIGNATURE_FAILURE: c_long = 7;
+pub const X509_V_ERR_CRL_SIGNATURE_FAILURE: c_long = 8;
+pub const X509_V_ERR_CERT_NOT_YET_VALID: c_long = 9;
+pub const X509_V_ERR_CERT_HAS_EXPIRED: c_long = 10;
+pub const X509_V_ERR_CRL_NOT_YET_VALID: c_long = 11;
+pub const X509_V_ERR_CRL_HAS_EXPIRED: c_long = 12;
+pub const X509_V_ERR_DEPTH_ZERO_SELF_SIGNED_CERT: c_long = 18;
+pub const X509_V_ERR_SELF_SIGNED_CERT_IN_CHAIN: c_long = 19;
+pub const X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT_LOCALLY: c_long = 20;
+pub const X509_V_ERR_UNABLE_TO_VERIFY_LEAF_SIGNATURE: c_long = 21;
+pub const X509_V_ERR_CERT_CHAIN_TOO_LONG: c_long = 22;
+pub const X509_V_ERR_CERT_REVOKED: c_long = 23;
+pub const X509_V_ERR_INVALID_CA: c_long = 24;
+pub const X509_V_ERR_INVALID_PURPOSE: c_long = 26;
+pub const X509_V_ERR_CERT_UNTRUSTED: c_long = 27;
+pub const X509_V_ERR_UNABLE_TO_GET_CRL_ISSUER: c_long = 33;
+pub const X509_V_ERR_INVALID_CALL: c_long = 65;
+
+/// A generic stack, as used by the STACK_OF(T) types.
+pub type _STACK = c_void;
+
+/// GENERAL_NAME is one entry of a subjectAltName extension.
+/// d points to a type-specific value; for GEN_EMAIL, GEN_DNS, GEN_URI and
+/// GEN_IPADD it is an ASN1_STRING.
+#[repr(C)]
+pub struct GENERAL_NAME {
+    pub type_: c_int,
+    pub d: *mut c_void,
+}
+
+pub const GEN_EMAIL: c_int = 1;
+pub const GEN_DNS: c_int = 2;
+pub const GEN_URI: c_int = 6;
+pub const GEN_IPADD: c_int = 7;
+
+pub const NID_rsaEncryption: c_int = 6;
+pub const NID_key_usage: c_int = 83;
+pub const NID_subject_alt_name: c_int = 85;
+pub const NID_ext_key_usage: c_int = 126;
+pub const NID_server_auth: c_int = 129;
+pub const NID_client_auth: c_int = 130;
+pub const NID_code_sign: c_int = 131;
+pub const NID_email_protect: c_int = 132;
+pub const NID_time_stamp: c_int = 133;
+pub const NID_OCSP_sign: c_int = 180;
+pub const NID_anyExtendedKeyUsage: c_int = 910;
+pub const NID_dsa: c_int = 116;
+pub const NID_X9_62_id_ecPublicKey: c_int = 408;
+pub const NID_ED25519: c_int = 949;
+
+// X509_NAME_print_ex flags for RFC 2253 output
+pub const XN_FLAG_RFC2253: c_ulong = 0x1110317;
+
+// key usage bits, as in the first two bytes of the keyUsage bit string
+pub const KU_DIGITAL_SIGNATURE: u32 = 0x0080;
+pub const KU_NON_REPUDIATION: u32 = 0x0040;
+pub const KU_KEY_ENCIPHERMENT: u32 = 0x0020;
+pub const KU_DATA_ENCIPHERMENT: u32 = 0x0010;
+pub const KU_KEY_AGREEMENT: u32 = 0x0008;
+pub const KU_KEY_CERT_SIGN: u32 = 0x0004;
+pub const KU_CRL_SIGN: u32 = 0x0002;
+pub const KU_ENCIPHER_ONLY: u32 = 0x0001;
+pub const KU_DECIPHER_ONLY: u32 = 0x8000;
+
+// extended key usage bits, one for each known purpose
+pub const XKU_SSL_SERVER: u32 = 0x1;
+pub const XKU_SSL_CLIENT: u32 = 0x2;
+pub const XKU_SMIME: u32 = 0x4;
+pub const XKU_CODE_SIGN: u32 = 0x8;
+pub const XKU_OCSP_SIGN: u32 = 0x20;
+pub const XKU_TIMESTAMP: u32 = 0x40;
+pub const XKU_ANYEKU: u32 = 0x100;
+
+pub const EVP_MAX_MD_SIZE: usize = 64;
+
+boringssl! {
+    /// X509_free decrements the reference count of x509 and frees it when it
+    /// reaches zero.
+    pub fn X509_free(x509: *mut X509);
+
+    /// X509_up_ref increments the reference count of x509 and returns one.
+    pub fn X509_up_ref(x509: *mut X509) -> c_int;
+
+    /// d2i_X509 parses a DER-encoded certificate from len bytes at *inp and
+    /// advances *inp. If out is NULL, a new X509 is returned, or NULL on error.
+    pub fn d2i_X509(out: *mut *mut X509, inp: *mut *const u8, len: c_long) -> *mut X509;
+
+    /// i2d_X509 DER-encodes x509. If outp is not NULL, it writes the encoding
+    /// to *outp and advances it. It returns the length of the encoding, or a
+    /// negative number on error.
+    pub fn i2d_X509(x509: *mut X509, outp: *mut *mut u8) -> c_int;
+
+    /// PEM_read_bio_X509 reads a PEM-encoded certificate from bio.
+    /// It returns NULL on error.
+    pub fn PEM_read_bio_X509(bio: *mut BIO,
+                             out: *mut *mut X509,
+                             cb: *mut c_void,
+                             u: *mut c_void)
+                             -> *mut X509;
+
+    /// PEM_write_bio_X509 writes x509 to bio in PEM format.
+    /// It returns one on success and zero on error.
+    pub fn PEM_write_bio_X509(bio: *mut BIO, x509: *mut X509) -> c_int;
+
+    pub fn X509_get_subject_name(x509: *const X509) -> *mut X509_NAME;
+    pub fn X509_get_issuer_name(x509: *const X509) -> *mut X509_NAME;
+    pub fn X509_get_serialNumber(x509: *mut X509) -> *mut ASN1_INTEGER;
+
+    /// X509_get_pubkey returns the certificate's public key with its reference
+    /// count incremented, or NULL on error. The caller must release it with
+    /// EVP_PKEY_free.
+    pub fn X509_get_pubkey(x509: *mut X509) -> *mut EVP_PKEY;
+
+    /// X509_get_ext_d2i decodes the extension identified by nid.
+    /// It returns NULL if the extension is absent or invalid.
+    pub fn X509_get_ext_d2i(x509: *const X509,
+                            nid: c_int,
+                            out_critical: *mut c_int,
+                            out_idx: *mut c_int)
+                            -> *mut c_void;
+
+    /// X509_digest hashes x509's DER encoding with md and writes the result to
+    /// out, which must have room for EVP_MAX_MD_SIZE bytes.
+    /// It returns one on success and zero on error.
+    pub fn X509_digest(x509: *const X509,
+                       md: *const EVP_MD,
+                       out: *mut u8,
+                       out_len: *mut c_uint)
+                       -> c_int;
+
+    /// X509_NAME_print_ex writes a human-readable representation of name to
+    /// bio, formatted according to flags. It returns the number of bytes
+    /// written, or a negative number on error.
+    pub fn X509_NAME_print_ex(bio: *mut BIO,
+                              name: *const X509_NAME,
+                              indent: c_int,
+                              flags: c_ulong)
+                              -> c_int;
+
+    pub fn GENERAL_NAMES_free(names: *mut _STACK);
+    pub fn EXTENDED_KEY_USAGE_free(eku: *mut _STACK);
+    pub fn ASN1_BIT_STRING_free(s: *mut ASN1_BIT_STRING);
+
+    /// OBJ_obj2nid returns the NID of obj, or NID_undef if it is unknown.
+    pub fn OBJ_obj2nid(obj: *const ASN1_OBJECT) -> c_int;
+
+    pub fn ASN1_STRING_get0_data(s: *const ASN1_STRING) -> *const u8;
+    pub fn ASN1_STRING_length(s: *const ASN1_STRING) -> c_int;
+
+    pub fn EVP_PKEY_free(pkey: *mut EVP_PKEY);
+    pub fn EVP_PKEY_up_ref(pkey: *mut EVP_PKEY) -> c_int;
+
+    /// PEM_read_bio_PrivateKey reads a PEM-encoded private key from bio.
+    /// It returns NULL on error.
+    pub fn PEM_read_bio_PrivateKey(bio: *mut BIO,
+                                   out: *mut *mut EVP_PKEY,
+                                   cb: *mut c_void,
+                                   u: *mut c_void)
+                                   -> *mut EVP_PKEY;
+
+    /// d2i_AutoPrivateKey parses a DER-encoded private key from len bytes at
+    /// *inp, detecting its type, and advances *inp. It returns NULL on error.
+    pub fn d2i_AutoPrivateKey(out: *mut *mut EVP_PKEY, inp: *mut *const u8, len: c_long)
+                              -> *mut EVP_PKEY;
+    pub fn EVP_PKEY_id(pkey: *const EVP_PKEY) -> c_int;
+    pub fn EVP_PKEY_bits(pkey: *const EVP_PKEY) -> c_int;
+    pub fn EVP_sha256() -> *const EVP_MD;
+
+    pub fn EVP_MD_CTX_new() -> *mut EVP_MD_CTX;
+    pub fn EVP_MD_CTX_free(ctx: *mut EVP_MD_CTX);
+
+    /// EVP_DigestSignInit sets up ctx for a signing operation with type and
+    /// pkey. If pctx is not NULL, the EVP_PKEY_CTX of the signing operation is
+    /// written to *pctx. It returns one on success and zero on error.
+    pub fn EVP_DigestSignInit(ctx: *mut EVP_MD_CTX,
+                              pctx: *mut *mut EVP_PKEY_CTX,
+                              type_: *const EVP_MD,
+                              e: *mut c_void,
+                              pkey: *mut EVP_PKEY)
+                              -> c_int;
+
+    pub fn EVP_PKEY_CTX_set_rsa_padding(ctx: *mut EVP_PKEY_CTX, padding: c_int) -> c_int;
+    pub fn EVP_PKEY_CTX_set_rsa_pss_saltlen(ctx: *mut EVP_PKEY_CTX, salt_len: c_int) -> c_int;
+
+    pub fn X509_verify_cert_error_string(err: c_long) -> *const c_char;
+
+    pub fn X509_CRL_free(crl: *mut X509_CRL);
+    pub fn X509_CRL_up_ref(crl: *mut X509_CRL) -> c_int;
+
+    /// d2i_X509_CRL parses a DER-encoded CRL from len bytes at *inp and
+    /// advances *inp. If out is NULL, a new X509_CRL is returned, or NULL on
+    /// error.
+    pub fn d2i_X509_CRL(out: *mut *mut X509_CRL, inp: *mut *const u8, len: c_long)
+                        -> *mut X509_CRL;
+
+    /// PEM_read_bio_X509_CRL reads a PEM-encoded CRL from bio.
+    /// It returns NULL on error.
+    pub fn PEM_read_bio_X509_CRL(bio: *mut BIO,
+                                 out: *mut *mut X509_CRL,
+                                 cb: *mut c_void,
+                                 u: *mut c_void)
+                                 -> *mut X509_CRL;
+
+    /// X509_STORE_add_cert adds x509 to store as a trust anchor.
+    /// It returns one on success and zero on error.
+    pub fn X509_STORE_add_cert(store: *mut X509_STORE, x509: *mut X509) -> c_int;
+
+    /// X509_STORE_add_crl adds crl to store. It returns one on success and
+    /// zero on error.
+    pub fn X509_STORE_add_crl(store: *mut X509_STORE, crl: *mut X509_CRL) -> c_int;
+
+    /// X509_STORE_set_flags sets the X509_V_FLAG_* values in flags on store's
+    /// verification parameters. It returns one.
+    pub fn X509_STORE_set_flags(store: *mut X509_STORE, flags: c_ulong) -> c_int;
+
+    /// X509_STORE_get0_param returns store's verification parameters.
+    /// The store retains ownership of the result.
+    pub fn X509_STORE_get0_param(store: *mut X509_STORE) -> *mut X509_VERIFY_PARAM;
+
+    /// X509_VERIFY_PARAM_clear_flags clears the X509_V_FLAG_* values in flags
+    /// on param. It returns one.
+    pub fn X509_VERIFY_PARAM_clear_flags(param: *mut X509_VERIFY_PARAM, flags: c_ulong) -> c_int;
+
+    pub fn sk_num(sk: *const _STACK) -> usize;
+    pub fn sk_value(sk: *const _STACK, i: usize) -> *mut c_void;
 }
 
 
@@ -175,7 +1223,7 @@ pub const SSL_ERROR_PENDING_SESSION: c_int = 11;
 pub const SSL_ERROR_PENDING_CERTIFICATE: c_int = 12;
 pub const SSL_ERROR_WANT_PRIVATE_KEY_OPERATION: c_int = 13;
 
-extern "C" {
+boringssl! {
     pub fn ERR_get_error() -> u32;
     pub fn ERR_peek_error() -> u32;
     pub fn ERR_lib_error_string(err: u32) -> *const c_char;
@@ -0,0 +1,102 @@
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_uint};
+use std::ptr;
+use std::slice;
+use std::str;
+
+use ffi;
+use server_name;
+use X509;
+
+/// ConnectionInfo is a snapshot of the parameters negotiated by a handshake,
+/// as returned by Client::info and Server::info.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// The protocol version, e.g. 0x0303 for TLS 1.2.
+    pub version: u16,
+
+    /// The IETF name of the cipher suite.
+    pub cipher: Option<&'static str>,
+
+    /// The name of the group used for key exchange, e.g. "X25519".
+    pub group: Option<&'static str>,
+
+    /// The name of the signature algorithm used by the peer.
+    pub signature_algorithm: Option<&'static str>,
+
+    /// The protocol selected with ALPN.
+    pub alpn_protocol: Option<Vec<u8>>,
+
+    /// Whether the handshake resumed a previous session.
+    pub session_resumed: bool,
+
+    /// Whether early data was accepted.
+    pub early_data_accepted: bool,
+
+    /// The hostname requested in the server_name extension.
+    pub server_name: Option<String>,
+
+    /// The SHA-256 digest of the peer's leaf certificate.
+    pub peer_certificate_sha256: Option<[u8; 32]>,
+
+    /// Whether the handshake returned early with False Start.
+    pub false_started: bool,
+}
+
+impl ConnectionInfo {
+    pub(crate) unsafe fn get(ssl: *const ffi::SSL, false_started: bool) -> ConnectionInfo {
+        let cipher = ffi::SSL_get_current_cipher(ssl);
+        let cipher = if cipher.is_null() {
+            None
+        } else {
+            static_str(ffi::SSL_CIPHER_standard_name(cipher))
+        };
+
+        let group = match ffi::SSL_get_curve_id(ssl) {
+            0 => None,
+            id => static_str(ffi::SSL_get_curve_name(id)),
+        };
+
+        let signature_algorithm = match ffi::SSL_get_peer_signature_algorithm(ssl) {
+            0 => None,
+            sigalg => static_str(ffi::SSL_get_signature_algorithm_name(sigalg, 0)),
+        };
+
+        let mut alpn = ptr::null();
+        let mut alpn_len: c_uint = 0;
+        ffi::SSL_get0_alpn_selected(ssl, &mut alpn, &mut alpn_len);
+        let alpn_protocol = if alpn.is_null() || alpn_len == 0 {
+            None
+        } else {
+            Some(slice::from_raw_parts(alpn, alpn_len as usize).to_vec())
+        };
+
+        let peer_cert = ffi::SSL_get_peer_certificate(ssl);
+        let peer_certificate_sha256 = if peer_cert.is_null() {
+            None
+        } else {
+            X509::from_ptr(peer_cert).sha256_fingerprint().ok()
+        };
+
+        ConnectionInfo {
+            version: ffi::SSL_version(ssl) as u16,
+            cipher: cipher,
+            group: group,
+            signature_algorithm: signature_algorithm,
+            alpn_protocol: alpn_protocol,
+            session_resumed: ffi::SSL_session_reused(ssl) == 1,
+            early_data_accepted: ffi::SSL_early_data_accepted(ssl) == 1,
+            server_name: server_name::get_servername(ssl).map(|name| name.to_owned()),
+            peer_certificate_sha256: peer_certificate_sha256,
+            false_started: false_started,
+        }
+    }
+}
+
+/// Converts a string constant owned by BoringSSL.
+unsafe fn static_str(s: *const c_char) -> Option<&'static str> {
+    if s.is_null() {
+        return None;
+    }
+    str::from_utf8(CStr::from_ptr(s).to_bytes()).ok()
+}
@@ -1,17 +1,65 @@
 #![allow(dead_code)]
 
-use std::os::raw::{c_int, c_void};
+use std::os::raw::{c_char, c_int, c_long, c_uint, c_void};
 use std::os::unix::io::RawFd;
 use std::ffi::{CString, CStr};
+use std::mem;
+use std::ptr;
+#[cfg(feature = "quic")]
+use std::slice;
 use std::str;
+use std::sync::{Mutex, Once};
+use std::time::Duration;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
 use std::result;
 use std::error;
 use std::fmt;
 
+#[cfg(boringssl_sys)]
+mod abi;
+mod alert;
+mod bio;
+mod client_hello;
 mod ffi;
+mod info;
+mod pkey;
+#[cfg(boringssl_private_key_method)]
+mod private_key;
+#[cfg(feature = "quic")]
+mod quic;
+mod server_name;
+mod session;
+/// Bindings for the full API of BoringSSL's ssl.h, x509.h, evp.h and err.h,
+/// generated by gen-bindings.sh. Unlike the rest of this crate, they give no
+/// safety guarantees. build.rs enables them once src/sys.rs exists, unless
+/// the symbols are prefixed.
+#[cfg(boringssl_sys)]
+#[allow(non_camel_case_types, non_snake_case, non_upper_case_globals, dead_code, clippy::all)]
+pub mod sys;
+mod trace;
+mod x509;
+
+pub use alert::{Alert, AlertLevel, AlertDescription};
+pub use bio::Bio;
+pub use client_hello::{ClientHello, SelectCertificateResult};
+pub use info::ConnectionInfo;
+pub use pkey::PrivateKey;
+#[cfg(boringssl_private_key_method)]
+pub use private_key::{PrivateKeyMethod, PrivateKeyResult};
+#[cfg(feature = "quic")]
+pub use quic::{EncryptionLevel, QuicMethod};
+pub use server_name::{ServerNameRequest, ServerNameAction};
+pub use session::Session;
+pub use trace::{InfoEvent, Message, Direction, ContentType};
+pub use x509::{X509, X509Crl, CrlCheck, VerifyError, KeyAlgorithm, SubjectAltName, KeyUsage,
+               ExtendedKeyUsage};
 
 /// TLS Versions
 pub use self::ffi::{TLS1_VERSION, TLS1_1_VERSION, TLS1_2_VERSION, TLS1_3_VERSION};
+/// DTLS Versions
+pub use self::ffi::{DTLS1_VERSION, DTLS1_2_VERSION};
 
 #[derive(Debug)]
 pub enum VerifyMode {
@@ -26,7 +74,13 @@ pub enum Error {
 
     /// The operation failed within the library.
     /// The caller may inspect the error queue for more information.
-    Ssl,
+    /// If the failure was accompanied by a fatal alert, sent or received, it
+    /// is attached.
+    Ssl(Option<Alert>),
+
+    /// The operation failed because the peer's certificate could not be
+    /// verified.
+    Verify(VerifyError),
 
     /// The operation failed attempting to read from the transport.
     /// The caller may retry the operation when the transport is ready for reading.
@@ -69,11 +123,10 @@ pub enum Error {
     /// The caller may retry the operation when lookup has completed.
     PendingSession,
 
-    /// The operation failed because the early callback indicated certificate
-    /// lookup was incomplete.
-    /// The caller may retry the operation when lookup has completed.
-    /// Note: when the operation is retried, the early callback will not be
-    /// called a second time.
+    /// The operation failed because the select certificate callback indicated
+    /// certificate lookup was incomplete.
+    /// The caller may retry the operation when lookup has completed, which
+    /// runs the callback again.
     PendingCertificate,
 
     /// The operation failed because a private key operation was unfinished.
@@ -81,31 +134,227 @@ pub enum Error {
     /// complete.
     WantPrivateKeyOperation,
 
+    /// SSL_get_error returned a value unknown to this library, e.g. one added
+    /// by a newer BoringSSL.
+    Unknown(i32),
+
     AllocationFailed, // TODO
 }
 
 pub type Result<T> = result::Result<T, Error>;
 
-// TODO lock on first connection
+/// Per-context state, reachable from callbacks through the SSL_CTX's ex_data.
+/// It is freed together with the SSL_CTX, so it outlives every connection
+/// created from the context.
+#[derive(Default)]
+struct CtxState {
+    server_name_callback: Option<Box<server_name::Callback>>,
+    select_certificate_callback: Option<Box<client_hello::Callback>>,
+    #[cfg(boringssl_private_key_method)]
+    private_key_method: Option<Box<private_key::Factory>>,
+    channel_id_callback: Option<Box<ChannelIdCallback>>,
+    keylog_callback: Option<Box<KeylogCallback>>,
+    info_callback: Option<Box<trace::InfoCallback>>,
+    msg_callback: Option<Box<trace::MsgCallback>>,
+    new_session_callback: Option<Box<session::Callback>>,
+
+    /// The server's ALPN protocols in wire format, in order of preference.
+    alpn_protocols: Option<Vec<u8>>,
+}
+
+/// Returns the CtxState of the SSL_CTX ssl currently belongs to, if any.
+unsafe fn ctx_state<'a>(ssl: *const ffi::SSL) -> Option<&'a CtxState> {
+    let ctx = ffi::SSL_get_SSL_CTX(ssl);
+    (ffi::SSL_CTX_get_ex_data(ctx, ctx_state_index()) as *const CtxState).as_ref()
+}
+
+extern "C" fn keylog_callback(ssl: *const ffi::SSL, line: *const c_char) {
+    let callback = match unsafe { ctx_state(ssl) } {
+        Some(&CtxState { keylog_callback: Some(ref callback), .. }) => callback,
+        _ => return,
+    };
+    if let Ok(line) = str::from_utf8(unsafe { CStr::from_ptr(line).to_bytes() }) {
+        callback(line);
+    }
+}
+
+type ChannelIdCallback = dyn Fn(Option<&str>) -> Option<PrivateKey> + Send + Sync;
+type KeylogCallback = dyn Fn(&str) + Send + Sync;
+
+extern "C" fn channel_id_callback(ssl: *mut ffi::SSL, out_pkey: *mut *mut ffi::EVP_PKEY) {
+    let callback = match unsafe { ctx_state(ssl) } {
+        Some(&CtxState { channel_id_callback: Some(ref callback), .. }) => callback,
+        _ => return,
+    };
+    let hostname = unsafe { server_name::get_servername(ssl) };
+    if let Some(key) = callback(hostname) {
+        unsafe { *out_pkey = key.into_ptr() };
+    }
+}
+
+extern "C" fn alpn_select_callback(ssl: *mut ffi::SSL,
+                                   out: *mut *const u8,
+                                   out_len: *mut u8,
+                                   in_: *const u8,
+                                   in_len: c_uint,
+                                   _arg: *mut c_void)
+                                   -> c_int {
+    let protos = match unsafe { ctx_state(ssl) } {
+        Some(&CtxState { alpn_protocols: Some(ref protos), .. }) => protos,
+        _ => return ffi::SSL_TLSEXT_ERR_NOACK,
+    };
+    // select the server's most preferred protocol the client offered
+    let ret_code = unsafe {
+        ffi::SSL_select_next_proto(out as *mut *mut u8,
+                                   out_len,
+                                   protos.as_ptr(),
+                                   protos.len() as c_uint,
+                                   in_,
+                                   in_len)
+    };
+    match ret_code {
+        ffi::OPENSSL_NPN_NEGOTIATED => ffi::SSL_TLSEXT_ERR_OK,
+        _ => ffi::SSL_TLSEXT_ERR_NOACK,
+    }
+}
+
+/// Encodes protocols in the ALPN wire format.
+fn alpn_wire_format(protos: &[&[u8]]) -> Result<Vec<u8>> {
+    let mut wire = Vec::new();
+    for proto in protos {
+        if proto.is_empty() || proto.len() > 255 {
+            return Err(Error::Ssl(None));
+        }
+        wire.push(proto.len() as u8);
+        wire.extend_from_slice(proto);
+    }
+    Ok(wire)
+}
+
+/// Returns the ex_data index under which a context's CtxState is stored.
+fn ctx_state_index() -> c_int {
+    static INIT: Once = Once::new();
+    static mut INDEX: c_int = -1;
+    unsafe {
+        INIT.call_once(|| {
+            INDEX = ffi::SSL_CTX_get_ex_new_index(0,
+                                                  ptr::null_mut(),
+                                                  ptr::null_mut(),
+                                                  ptr::null_mut(),
+                                                  Some(free_ctx_state));
+            assert!(INDEX >= 0);
+        });
+        INDEX
+    }
+}
+
+extern "C" fn free_ctx_state(_parent: *mut c_void,
+                             ptr: *mut c_void,
+                             _ad: *mut c_void,
+                             _index: c_int,
+                             _argl: c_long,
+                             _argp: *mut c_void) {
+    if !ptr.is_null() {
+        drop(unsafe { Box::from_raw(ptr as *mut CtxState) });
+    }
+}
+
+/// Context holds the configuration shared by connections.
+/// It is immutable: it is configured with a ContextBuilder beforehand, so
+/// connections may be created from any number of threads at once.
+/// Cloning is cheap and returns another reference to the same SSL_CTX.
+///
+/// ```compile_fail
+/// let ctx = boringssl::Context::new().unwrap();
+/// ctx.set_min_version(boringssl::TLS1_2_VERSION);
+/// ```
 pub struct Context {
     ctx: *mut ffi::SSL_CTX,
 }
 
+// The SSL_CTX is thread-safe once it is no longer being configured, and
+// every callback stored in its CtxState is Send + Sync.
+unsafe impl Send for Context {}
+unsafe impl Sync for Context {}
+
 impl Drop for Context {
     fn drop(&mut self) {
         unsafe { ffi::SSL_CTX_free(self.ctx) }
     }
 }
 
+impl Clone for Context {
+    fn clone(&self) -> Context {
+        unsafe { ffi::SSL_CTX_up_ref(self.ctx) };
+        Context { ctx: self.ctx }
+    }
+}
+
 impl Context {
+    /// Returns a Context with the default configuration.
     pub fn new() -> Result<Context> {
+        ContextBuilder::new().map(ContextBuilder::build)
+    }
+
+    /// Returns a builder to configure a new Context.
+    pub fn builder() -> Result<ContextBuilder> {
+        ContextBuilder::new()
+    }
+}
+
+/// ContextBuilder configures a Context. It is turned into the immutable,
+/// shareable Context with build, after which the configuration can no longer
+/// be changed.
+pub struct ContextBuilder {
+    ctx: *mut ffi::SSL_CTX,
+}
+
+impl Drop for ContextBuilder {
+    fn drop(&mut self) {
+        unsafe { ffi::SSL_CTX_free(self.ctx) }
+    }
+}
+
+impl ContextBuilder {
+    pub fn new() -> Result<ContextBuilder> {
+        ContextBuilder::with_method(unsafe { ffi::TLS_method() })
+    }
+
+    /// Returns a builder for a DTLS context. Connections created from it
+    /// should use a datagram transport, e.g. Bio::new_udp, and must call
+    /// handle_dtls_timeout when dtls_timeout expires.
+    /// Versions are configured with DTLS1_VERSION and DTLS1_2_VERSION.
+    pub fn new_dtls() -> Result<ContextBuilder> {
+        ContextBuilder::with_method(unsafe { ffi::DTLS_method() })
+    }
+
+    fn with_method(method: *const ffi::SSL_METHOD) -> Result<ContextBuilder> {
+        let ctx = unsafe { ffi::SSL_CTX_new(method) };
+        if ctx.is_null() {
+            return Err(Error::AllocationFailed);
+        }
+        Ok(ContextBuilder { ctx: ctx })
+    }
+
+    /// Finishes the configuration.
+    pub fn build(self) -> Context {
+        let ctx = self.ctx;
+        mem::forget(self);
+        Context { ctx: ctx }
+    }
+
+    /// Returns the CtxState attached to the SSL_CTX, creating it if needed.
+    fn state(&mut self) -> &mut CtxState {
         unsafe {
-            let method = ffi::TLS_method();
-            let ctx = ffi::SSL_CTX_new(method);
-            if ctx.is_null() {
-                return Err(Error::AllocationFailed);
+            let mut state = ffi::SSL_CTX_get_ex_data(self.ctx, ctx_state_index()) as *mut CtxState;
+            if state.is_null() {
+                state = Box::into_raw(Box::new(CtxState::default()));
+                if ffi::SSL_CTX_set_ex_data(self.ctx, ctx_state_index(), state as *mut c_void) != 1 {
+                    drop(Box::from_raw(state));
+                    panic!("{:?}", SslError::get());
+                }
             }
-            Ok(Context { ctx: ctx })
+            &mut *state
         }
     }
 
@@ -124,6 +373,12 @@ impl Context {
         };
     }
 
+    pub fn set_max_version(&mut self, version: u16) {
+        unsafe {
+            ffi::SSL_CTX_set_max_version(self.ctx, version);
+        };
+    }
+
     pub fn set_verify(&mut self, mode: VerifyMode) {
         unsafe {
             ffi::SSL_CTX_set_verify(self.ctx, mode as c_int, None);
@@ -149,76 +404,348 @@ impl Context {
         }
     }
 
-    // pub fn connect_socket(&self, fd: RawFd) -> Result<Connection> {
-    // let conn = try!(Connection::new(self));
-    //
-    // let bio = try!(Bio::new_socket(fd));
-    // conn.set_bio(bio);
-    //
-    // if conn.set_fd(fd) != 1 {
-    // return Err(Error::AllocationFailed);
-    // }
-    //
-    // let ret = conn.connect();
-    // println!("ret code {:?}", ret);
-    //
-    // match ret {
-    // 1 => Ok(conn),
-    // n => Err(conn.get_error(n)),
-    // }
-    // }
+    /// Sets a callback that is run on the client when the server requests a
+    /// TLS Channel ID and no key was set with Client::set_channel_id_key.
+    /// It is passed the hostname set with Client::set_hostname and returns a
+    /// P-256 key. If it returns None, the handshake fails with
+    /// Error::WantChannelIdLookup; the caller may then set a key with
+    /// Client::set_channel_id_key and retry.
+    pub fn set_channel_id_callback<F>(&mut self, callback: F)
+        where F: Fn(Option<&str>) -> Option<PrivateKey> + Send + Sync + 'static
+    {
+        self.state().channel_id_callback = Some(Box::new(callback));
+        unsafe { ffi::SSL_CTX_set_channel_id_cb(self.ctx, Some(channel_id_callback)) };
+    }
+
+    /// Sets the leaf certificate presented to the peer.
+    pub fn set_certificate(&mut self, cert: &X509) -> Result<()> {
+        match unsafe { ffi::SSL_CTX_use_certificate(self.ctx, cert.as_ptr()) } {
+            1 => Ok(()),
+            _ => Err(Error::Ssl(None)),
+        }
+    }
+
+    /// Sets the private key matching the certificate set with set_certificate.
+    pub fn set_private_key(&mut self, key: &PrivateKey) -> Result<()> {
+        unsafe {
+            if ffi::SSL_CTX_use_PrivateKey(self.ctx, key.as_ptr()) != 1 ||
+               ffi::SSL_CTX_check_private_key(self.ctx) != 1 {
+                return Err(Error::Ssl(None));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets a callback that is run on the server once the client's
+    /// server_name extension has been parsed, the counterpart of
+    /// Client::set_hostname. It may switch the connection to a different
+    /// Context to select its certificate, or reject the handshake with an
+    /// unrecognized_name alert.
+    pub fn set_server_name_callback<F>(&mut self, callback: F)
+        where F: Fn(&mut ServerNameRequest) -> ServerNameAction + Send + Sync + 'static
+    {
+        let state = {
+            let state = self.state();
+            state.server_name_callback = Some(Box::new(callback));
+            state as *mut CtxState as *mut c_void
+        };
+        unsafe {
+            ffi::SSL_CTX_set_tlsext_servername_callback(self.ctx,
+                                                        Some(server_name::callback));
+            ffi::SSL_CTX_set_tlsext_servername_arg(self.ctx, state);
+        }
+    }
+
+    /// Sets a callback that is run on the server when a ClientHello is
+    /// received, before any other ClientHello processing. It may inspect the
+    /// ClientHello and configure the connection, e.g. select its certificate.
+    /// Returning SelectCertificateResult::Retry pauses the handshake with
+    /// Error::PendingCertificate until it is retried.
+    pub fn set_select_certificate_callback<F>(&mut self, callback: F)
+        where F: Fn(&mut ClientHello) -> SelectCertificateResult + Send + Sync + 'static
+    {
+        self.state().select_certificate_callback = Some(Box::new(callback));
+        unsafe {
+            ffi::SSL_CTX_set_select_certificate_cb(self.ctx, Some(client_hello::callback));
+        }
+    }
+
+    /// Offloads the private key operations for the certificate set with
+    /// set_certificate to method, instead of using a PrivateKey.
+    /// Each connection uses its own clone of method.
+    #[cfg(boringssl_private_key_method)]
+    pub fn set_private_key_method<M>(&mut self, method: M)
+        where M: PrivateKeyMethod + Clone + Send + Sync + 'static
+    {
+        self.state().private_key_method = Some(Box::new(move || Box::new(method.clone())));
+        unsafe { ffi::SSL_CTX_set_private_key_method(self.ctx, &private_key::METHOD) };
+    }
+
+    /// Sets a callback that is passed the secrets of every connection created
+    /// from this context, one line at a time in the NSS key log format
+    /// (without trailing newline).
+    ///
+    /// This is only meant for debugging, e.g. to decrypt captured traffic with
+    /// Wireshark. Anyone with access to the logged lines can decrypt the
+    /// connections, so it must never be enabled in production.
+    pub fn set_keylog_callback<F>(&mut self, callback: F)
+        where F: Fn(&str) + Send + Sync + 'static
+    {
+        self.state().keylog_callback = Some(Box::new(callback));
+        unsafe { ffi::SSL_CTX_set_keylog_callback(self.ctx, Some(keylog_callback)) };
+    }
+
+    /// Appends the secrets of every connection created from this context to
+    /// the file named by the SSLKEYLOGFILE environment variable, as browsers
+    /// do. It returns false and does nothing if the variable is not set.
+    ///
+    /// This is only meant for debugging; see set_keylog_callback. The file
+    /// allows anyone who can read it to decrypt the logged connections.
+    pub fn set_keylog_file_from_env(&mut self) -> io::Result<bool> {
+        let path = match env::var_os("SSLKEYLOGFILE") {
+            Some(path) => path,
+            None => return Ok(false),
+        };
+        let file = try!(OpenOptions::new().create(true).append(true).open(path));
+        let file = Mutex::new(file);
+        self.set_keylog_callback(move |line| {
+            // lines must not interleave across connections
+            let mut file = file.lock().unwrap();
+            let _ = writeln!(file, "{}", line);
+        });
+        Ok(true)
+    }
+
+    /// Sets a callback that is run on handshake events of every connection
+    /// created from this context: handshake start and completion, alerts and
+    /// state transitions.
+    pub fn set_info_callback<F>(&mut self, callback: F)
+        where F: Fn(&InfoEvent) + Send + Sync + 'static
+    {
+        self.state().info_callback = Some(Box::new(callback));
+    }
+
+    /// Sets a callback that is passed every protocol message sent or received
+    /// on connections created from this context, including record headers.
+    /// The Display implementation of Message prints a transcript line.
+    pub fn set_msg_callback<F>(&mut self, callback: F)
+        where F: Fn(&Message) + Send + Sync + 'static
+    {
+        self.state().msg_callback = Some(Box::new(callback));
+        unsafe { ffi::SSL_CTX_set_msg_callback(self.ctx, Some(trace::msg_callback)) };
+    }
+
+    /// Sets a callback that is passed every new session of clients created
+    /// from this context, to be resumed with Client::set_session. In TLS 1.3,
+    /// sessions arrive after the handshake, when the client reads.
+    pub fn set_new_session_callback<F>(&mut self, callback: F)
+        where F: Fn(Session) + Send + Sync + 'static
+    {
+        self.state().new_session_callback = Some(Box::new(callback));
+        unsafe {
+            ffi::SSL_CTX_set_session_cache_mode(self.ctx, ffi::SSL_SESS_CACHE_CLIENT);
+            ffi::SSL_CTX_sess_set_new_cb(self.ctx, Some(session::new_session_callback));
+        }
+    }
+
+    /// Sets the ALPN protocols the server supports, in order of preference.
+    /// The first of them the client offers is selected; if there is none,
+    /// ALPN is not negotiated.
+    pub fn set_alpn_protocols(&mut self, protos: &[&[u8]]) -> Result<()> {
+        let protos = try!(alpn_wire_format(protos));
+        self.state().alpn_protocols = Some(protos);
+        unsafe {
+            ffi::SSL_CTX_set_alpn_select_cb(self.ctx, Some(alpn_select_callback), ptr::null_mut())
+        };
+        Ok(())
+    }
+
+    /// Adds cert to the trust anchors used to verify peer certificates.
+    pub fn add_trusted_cert(&mut self, cert: &X509) -> Result<()> {
+        let ret_code = unsafe {
+            let store = ffi::SSL_CTX_get_cert_store(self.ctx);
+            ffi::X509_STORE_add_cert(store, cert.as_ptr())
+        };
+        match ret_code {
+            1 => Ok(()),
+            _ => Err(Error::Ssl(None)),
+        }
+    }
+
+    /// Adds crl to the verification store. CRLs are only consulted once
+    /// checking has been enabled with set_crl_check.
+    pub fn add_crl(&mut self, crl: &X509Crl) -> Result<()> {
+        let ret_code = unsafe {
+            let store = ffi::SSL_CTX_get_cert_store(self.ctx);
+            ffi::X509_STORE_add_crl(store, crl.as_ptr())
+        };
+        match ret_code {
+            1 => Ok(()),
+            _ => Err(Error::Ssl(None)),
+        }
+    }
+
+    /// Enables revocation checking against the CRLs in the verification store,
+    /// replacing the previous setting. Verification fails if a checked
+    /// certificate has no CRL from its issuer.
+    pub fn set_crl_check(&mut self, check: CrlCheck) {
+        let flags = match check {
+            CrlCheck::Leaf => ffi::X509_V_FLAG_CRL_CHECK,
+            CrlCheck::Chain => ffi::X509_V_FLAG_CRL_CHECK | ffi::X509_V_FLAG_CRL_CHECK_ALL,
+        };
+        unsafe {
+            // setting flags only adds to them
+            let store = ffi::SSL_CTX_get_cert_store(self.ctx);
+            ffi::X509_VERIFY_PARAM_clear_flags(ffi::X509_STORE_get0_param(store),
+                                               ffi::X509_V_FLAG_CRL_CHECK |
+                                               ffi::X509_V_FLAG_CRL_CHECK_ALL);
+            ffi::X509_STORE_set_flags(store, flags);
+        }
+    }
 }
 
-pub struct Client {
+/// Per-connection state, reachable from callbacks through the SSL's ex_data.
+#[derive(Default)]
+struct ConnState {
+    /// The most recent alert sent or received on the connection.
+    last_alert: Option<Alert>,
+
+    /// The connection's private key method, set explicitly or instantiated
+    /// from the context on first use.
+    #[cfg(boringssl_private_key_method)]
+    private_key_method: Option<Box<dyn PrivateKeyMethod>>,
+
+    /// Whether the handshake returned early with False Start.
+    false_started: bool,
+
+    /// The connection's QUIC hooks, if it is used with QUIC.
+    #[cfg(feature = "quic")]
+    quic_method: Option<Box<dyn QuicMethod>>,
+}
+
+/// Returns the ex_data index under which a connection's ConnState is stored.
+fn conn_state_index() -> c_int {
+    static INIT: Once = Once::new();
+    static mut INDEX: c_int = -1;
+    unsafe {
+        INIT.call_once(|| {
+            INDEX = ffi::SSL_get_ex_new_index(0,
+                                              ptr::null_mut(),
+                                              ptr::null_mut(),
+                                              ptr::null_mut(),
+                                              None);
+            assert!(INDEX >= 0);
+        });
+        INDEX
+    }
+}
+
+/// Returns the ConnState attached to ssl, if any.
+unsafe fn conn_state<'a>(ssl: *const ffi::SSL) -> Option<&'a mut ConnState> {
+    let state = ffi::SSL_get_ex_data(ssl, conn_state_index()) as *mut ConnState;
+    state.as_mut()
+}
+
+/// The info callback of every connection. It records alerts and runs the
+/// context's info callback, which would otherwise be overridden.
+extern "C" fn info_callback(ssl: *const ffi::SSL, type_: c_int, value: c_int) {
+    if let Some(state) = unsafe { conn_state(ssl) } {
+        if let Some(alert) = Alert::from_info(type_, value) {
+            state.last_alert = Some(alert);
+        }
+    }
+    trace::info(ssl, type_, value);
+}
+
+/// The state shared by client and server connections.
+struct Connection {
     ssl: *mut ffi::SSL,
+    state: Box<ConnState>,
+
+    /// The context the connection was created from, released after the SSL.
+    ctx: Context,
 }
 
-impl Drop for Client {
+// An SSL may only be used from one thread at a time, which &mut self on every
+// operation guarantees; it is never shared, so Connection is not Sync. The
+// per-connection callbacks in ConnState are Send.
+unsafe impl Send for Connection {}
+
+impl Drop for Connection {
     fn drop(&mut self) {
-        println!("DROP"); // TODO
         unsafe { ffi::SSL_free(self.ssl) }
     }
 }
 
-impl Client {
-    fn new(ctx: &Context) -> Result<Client> {
+impl Connection {
+    fn new(ctx: &Context) -> Result<Connection> {
         let ssl = unsafe { ffi::SSL_new(ctx.ctx) };
         if ssl.is_null() {
             return Err(Error::AllocationFailed);
         }
+        let mut conn = Connection {
+            ssl: ssl,
+            state: Box::new(ConnState::default()),
+            ctx: ctx.clone(),
+        };
 
-        // configure as client
-        unsafe { ffi::SSL_set_connect_state(ssl) };
-        Ok(Client { ssl: ssl })
+        // record alerts through the info callback
+        let state = &mut *conn.state as *mut ConnState as *mut c_void;
+        if unsafe { ffi::SSL_set_ex_data(ssl, conn_state_index(), state) } != 1 {
+            return Err(Error::AllocationFailed);
+        }
+        unsafe { ffi::SSL_set_info_callback(ssl, Some(info_callback)) };
+        Ok(conn)
     }
 
-    pub fn new_socket(ctx: &Context, fd: RawFd) -> Result<Client> {
-        let mut conn = try!(Client::new(ctx));
-
-        // let bio = try!(Bio::new_socket(fd));
-        // conn.set_bio(bio);
+    fn set_bio(&mut self, bio: Bio) {
+        // SSL_set_bio takes a single reference when rbio and wbio are equal
+        let bio = bio.into_ptr();
+        unsafe { ffi::SSL_set_bio(self.ssl, bio, bio) };
+    }
 
-        if conn.set_fd(fd) != 1 {
-            return Err(Error::AllocationFailed);
+    fn set_fd(&mut self, fd: RawFd) -> Result<()> {
+        match unsafe { ffi::SSL_set_fd(self.ssl, fd) } {
+            1 => Ok(()),
+            _ => Err(Error::AllocationFailed),
         }
+    }
 
-        Ok(conn)
+    fn set_mtu(&mut self, mtu: u32) -> Result<()> {
+        match unsafe { ffi::SSL_set_mtu(self.ssl, mtu) } {
+            1 => Ok(()),
+            _ => Err(Error::Ssl(None)),
+        }
     }
 
-    fn set_bio(&mut self, bio: Bio) {
-        unsafe { ffi::SSL_set_bio(self.ssl, bio.bio, bio.bio) };
+    fn dtls_timeout(&self) -> Option<Duration> {
+        let mut out = ffi::timeval {
+            tv_sec: 0,
+            tv_usec: 0,
+        };
+        match unsafe { ffi::DTLSv1_get_timeout(self.ssl, &mut out) } {
+            1 => Some(Duration::new(out.tv_sec as u64, out.tv_usec as u32 * 1000)),
+            _ => None,
+        }
     }
 
-    fn set_fd(&mut self, fd: RawFd) -> c_int {
-        unsafe { ffi::SSL_set_fd(self.ssl, fd) }
+    fn handle_dtls_timeout(&mut self) -> Result<bool> {
+        match unsafe { ffi::DTLSv1_handle_timeout(self.ssl) } {
+            1 => Ok(true),
+            0 => Ok(false),
+            n => Err(self.get_error(n)),
+        }
     }
 
-    /// If <0 is returned, it must be called again when the underlying stream is
-    /// ready to contiue the handshake.
-    pub fn handshake(&mut self) -> Result<()> {
+    fn handshake(&mut self) -> Result<()> {
         let ret_code = unsafe { ffi::SSL_do_handshake(self.ssl) };
         match ret_code {
-            1 => Ok(()),
+            1 => {
+                if unsafe { ffi::SSL_in_false_start(self.ssl) } == 1 {
+                    self.state.false_started = true;
+                }
+                Ok(())
+            }
             n => Err(self.get_error(n)),
         }
     }
@@ -227,7 +754,15 @@ impl Client {
         let err_code = unsafe { ffi::SSL_get_error(self.ssl, ret_code) };
         match err_code {
             ffi::SSL_ERROR_NONE => Error::None,
-            ffi::SSL_ERROR_SSL => Error::Ssl,
+            ffi::SSL_ERROR_SSL => {
+                if let Some(err) = self.verify_failure() {
+                    return Error::Verify(err);
+                }
+                match self.state.last_alert {
+                    Some(alert) if alert.is_fatal() => Error::Ssl(Some(alert)),
+                    _ => Error::Ssl(None),
+                }
+            }
             ffi::SSL_ERROR_WANT_READ => Error::WantRead,
             ffi::SSL_ERROR_WANT_WRITE => Error::WantWrite,
             ffi::SSL_ERROR_WANT_X509_LOOKUP => Error::WantX509Lookup,
@@ -239,21 +774,164 @@ impl Client {
             ffi::SSL_ERROR_PENDING_SESSION => Error::PendingSession,
             ffi::SSL_ERROR_PENDING_CERTIFICATE => Error::PendingCertificate,
             ffi::SSL_ERROR_WANT_PRIVATE_KEY_OPERATION => Error::WantPrivateKeyOperation,
-            _ => unimplemented!(),
+            n => Error::Unknown(n),
         }
     }
 
-    /// Configures ssl to advertise name in the server_name extension (RFC 3546).
-    pub fn set_hostname(&mut self, hostname: &str) -> Result<()> {
-        let cstr = CString::new(hostname).unwrap();
-        let ret_code = unsafe { ffi::SSL_set_tlsext_host_name(self.ssl, cstr.as_ptr()) };
+    #[cfg(boringssl_private_key_method)]
+    fn set_private_key_method<M>(&mut self, method: M)
+        where M: PrivateKeyMethod + 'static
+    {
+        self.state.private_key_method = Some(Box::new(method));
+        unsafe { ffi::SSL_set_private_key_method(self.ssl, &private_key::METHOD) };
+    }
+
+    fn info(&self) -> ConnectionInfo {
+        unsafe { ConnectionInfo::get(self.ssl, self.state.false_started) }
+    }
+
+    #[cfg(feature = "quic")]
+    fn set_quic_method<M>(&mut self, method: M) -> Result<()>
+        where M: QuicMethod + 'static
+    {
+        self.state.quic_method = Some(Box::new(method));
+        match unsafe { ffi::SSL_set_quic_method(self.ssl, &quic::METHOD) } {
+            1 => Ok(()),
+            _ => Err(Error::Ssl(None)),
+        }
+    }
+
+    #[cfg(feature = "quic")]
+    fn provide_quic_data(&mut self, level: EncryptionLevel, data: &[u8]) -> Result<()> {
+        let ret_code = unsafe {
+            ffi::SSL_provide_quic_data(self.ssl, level.to_raw(), data.as_ptr(), data.len())
+        };
         match ret_code {
+            1 => Ok(()),
+            _ => Err(Error::Ssl(None)),
+        }
+    }
+
+    #[cfg(feature = "quic")]
+    fn process_quic_post_handshake(&mut self) -> Result<()> {
+        match unsafe { ffi::SSL_process_quic_post_handshake(self.ssl) } {
             1 => Ok(()),
             n => Err(self.get_error(n)),
         }
     }
 
-    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+    #[cfg(feature = "quic")]
+    fn quic_read_level(&self) -> EncryptionLevel {
+        EncryptionLevel::from_raw(unsafe { ffi::SSL_quic_read_level(self.ssl) })
+    }
+
+    #[cfg(feature = "quic")]
+    fn quic_write_level(&self) -> EncryptionLevel {
+        EncryptionLevel::from_raw(unsafe { ffi::SSL_quic_write_level(self.ssl) })
+    }
+
+    #[cfg(feature = "quic")]
+    fn set_quic_transport_params(&mut self, params: &[u8]) -> Result<()> {
+        let ret_code = unsafe {
+            ffi::SSL_set_quic_transport_params(self.ssl, params.as_ptr(), params.len())
+        };
+        match ret_code {
+            1 => Ok(()),
+            _ => Err(Error::Ssl(None)),
+        }
+    }
+
+    #[cfg(feature = "quic")]
+    fn peer_quic_transport_params(&self) -> Option<&[u8]> {
+        let mut params = ptr::null();
+        let mut len = 0;
+        unsafe {
+            ffi::SSL_get_peer_quic_transport_params(self.ssl, &mut params, &mut len);
+            if params.is_null() || len == 0 {
+                return None;
+            }
+            Some(slice::from_raw_parts(params, len))
+        }
+    }
+
+    fn peer_certificate(&self) -> Option<X509> {
+        let x509 = unsafe { ffi::SSL_get_peer_certificate(self.ssl) };
+        if x509.is_null() {
+            return None;
+        }
+        Some(unsafe { X509::from_ptr(x509) })
+    }
+
+    fn peer_cert_chain(&self) -> Vec<X509> {
+        unsafe { x509::stack_to_vec(ffi::SSL_get_peer_cert_chain(self.ssl)) }
+    }
+
+    fn verify_result(&self) -> result::Result<(), VerifyError> {
+        let err = unsafe { ffi::SSL_get_verify_result(self.ssl) };
+        match VerifyError::from_raw(err) {
+            None => Ok(()),
+            Some(err) => Err(err),
+        }
+    }
+
+    /// Returns why verifying the peer's certificate failed the handshake, or
+    /// None if it didn't. Before verification runs, and without a session,
+    /// the result is X509_V_ERR_INVALID_CALL; with VerifyMode::None a failed
+    /// verification is ignored and doesn't cause later errors.
+    fn verify_failure(&self) -> Option<VerifyError> {
+        if unsafe { ffi::SSL_get_verify_mode(self.ssl) } & ffi::SSL_VERIFY_PEER == 0 {
+            return None;
+        }
+        match self.verify_result() {
+            Err(VerifyError::Other(n)) if n as c_long == ffi::X509_V_ERR_INVALID_CALL => None,
+            Err(err) => Some(err),
+            Ok(()) => None,
+        }
+    }
+
+    fn export_keying_material(&self,
+                              label: &str,
+                              context: Option<&[u8]>,
+                              len: usize)
+                              -> Result<Vec<u8>> {
+        let mut out = vec![0; len];
+        let (context, context_len, use_context) = match context {
+            Some(context) => (context.as_ptr(), context.len(), 1),
+            None => (ptr::null(), 0, 0),
+        };
+        let ret_code = unsafe {
+            ffi::SSL_export_keying_material(self.ssl,
+                                            out.as_mut_ptr(),
+                                            out.len(),
+                                            label.as_ptr() as *const c_char,
+                                            label.len(),
+                                            context,
+                                            context_len,
+                                            use_context)
+        };
+        match ret_code {
+            1 => Ok(out),
+            _ => Err(Error::Ssl(None)),
+        }
+    }
+
+    fn tls_unique(&self) -> Option<Vec<u8>> {
+        // tls-unique is the first Finished message, at most 12 bytes in TLS 1.2
+        // and 36 bytes in SSL 3.0.
+        let mut out = vec![0; 36];
+        let mut len = 0;
+        let ret_code =
+            unsafe { ffi::SSL_get_tls_unique(self.ssl, out.as_mut_ptr(), &mut len, out.len()) };
+        match ret_code {
+            1 => {
+                out.truncate(len);
+                Some(out)
+            }
+            _ => None,
+        }
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
         let ret_code =
             unsafe { ffi::SSL_read(self.ssl, buf.as_ptr() as *mut c_void, buf.len() as c_int) };
         if ret_code > 0 {
@@ -263,12 +941,12 @@ impl Client {
         }
     }
 
-    pub fn pending(&mut self) -> usize {
+    fn pending(&mut self) -> usize {
         let num = unsafe { ffi::SSL_pending(self.ssl) };
         num as usize
     }
 
-    pub fn write(&mut self, buf: &[u8]) -> Result<usize> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
         let ret_code =
             unsafe { ffi::SSL_write(self.ssl, buf.as_ptr() as *const c_void, buf.len() as c_int) };
         if ret_code > 0 {
@@ -279,20 +957,467 @@ impl Client {
     }
 }
 
-pub struct Bio {
-    bio: *mut ffi::BIO,
+/// A TLS client connection.
+///
+/// A Client may be moved to another thread, e.g. into a worker thread or an
+/// async task, but not shared between threads: it is Send but not Sync.
+/// The callbacks of its Context run on whichever thread is driving the
+/// connection at the time.
+///
+/// ```compile_fail
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<boringssl::Client>();
+/// ```
+///
+/// The connection holds a reference to its Context, so the Context may be
+/// dropped while the connection is still in use.
+pub struct Client {
+    conn: Connection,
 }
 
-impl Bio {
-    fn new_socket(fd: RawFd) -> Result<Bio> {
-        unsafe {
-            let bio = ffi::BIO_new_socket(fd, ffi::BIO_NOCLOSE);
-            if bio.is_null() {
-                return Err(Error::AllocationFailed);
-            }
-            Ok(Bio { bio: bio })
+impl Client {
+    fn new(ctx: &Context) -> Result<Client> {
+        let conn = try!(Connection::new(ctx));
+
+        // configure as client
+        unsafe { ffi::SSL_set_connect_state(conn.ssl) };
+        Ok(Client { conn: conn })
+    }
+
+    pub fn new_socket(ctx: &Context, fd: RawFd) -> Result<Client> {
+        let mut client = try!(Client::new(ctx));
+
+        // let bio = try!(Bio::new_socket(fd));
+        // client.conn.set_bio(bio);
+
+        try!(client.conn.set_fd(fd));
+        Ok(client)
+    }
+
+    /// Creates a client that reads from and writes to bio, which the connection
+    /// takes ownership of. It is freed together with the connection unless
+    /// other handles to it remain.
+    ///
+    /// # Safety
+    ///
+    /// BIOs are not thread-safe. If handles to bio, or to the other end of
+    /// a BIO pair, are kept, the connection must not be moved to another
+    /// thread while they are in use.
+    pub unsafe fn new_bio(ctx: &Context, bio: Bio) -> Result<Client> {
+        let mut client = try!(Client::new(ctx));
+        client.conn.set_bio(bio);
+        Ok(client)
+    }
+
+    /// Creates a client for QUIC. The handshake has no transport; it is driven
+    /// through method and provide_quic_data instead. QUIC requires TLS 1.3
+    /// and ALPN.
+    #[cfg(feature = "quic")]
+    pub fn new_quic<M>(ctx: &Context, method: M) -> Result<Client>
+        where M: QuicMethod + 'static
+    {
+        let mut client = try!(Client::new(ctx));
+        try!(client.conn.set_quic_method(method));
+        Ok(client)
+    }
+
+    /// Returns the Context the client was created from.
+    pub fn context(&self) -> &Context {
+        &self.conn.ctx
+    }
+
+    /// If <0 is returned, it must be called again when the underlying stream is
+    /// ready to contiue the handshake.
+    pub fn handshake(&mut self) -> Result<()> {
+        self.conn.handshake()
+    }
+
+    /// Returns the most recent alert sent or received on the connection.
+    pub fn last_alert(&self) -> Option<Alert> {
+        self.conn.state.last_alert
+    }
+
+    /// Sets the path MTU for DTLS, excluding IP and UDP headers.
+    pub fn set_mtu(&mut self, mtu: u32) -> Result<()> {
+        self.conn.set_mtu(mtu)
+    }
+
+    /// Returns the time until the current DTLS handshake timeout expires, or
+    /// None if there is none. The caller should wait for the transport to
+    /// become readable no longer than that, then call handle_dtls_timeout.
+    pub fn dtls_timeout(&self) -> Option<Duration> {
+        self.conn.dtls_timeout()
+    }
+
+    /// Retransmits the previous DTLS handshake flight if the timeout expired.
+    /// It returns whether it did. It fails if too many timeouts expired
+    /// without progress.
+    pub fn handle_dtls_timeout(&mut self) -> Result<bool> {
+        self.conn.handle_dtls_timeout()
+    }
+
+    /// Offloads the private key operations of this connection to method,
+    /// overriding the context's private key or method.
+    #[cfg(boringssl_private_key_method)]
+    pub fn set_private_key_method<M>(&mut self, method: M)
+        where M: PrivateKeyMethod + 'static
+    {
+        self.conn.set_private_key_method(method)
+    }
+
+    /// Sets the protocols offered with ALPN, in order of preference.
+    pub fn set_alpn_protos(&mut self, protos: &[&[u8]]) -> Result<()> {
+        let protos = try!(alpn_wire_format(protos));
+        let ret_code = unsafe {
+            ffi::SSL_set_alpn_protos(self.conn.ssl, protos.as_ptr(), protos.len() as c_uint)
+        };
+        match ret_code {
+            0 => Ok(()),
+            _ => Err(Error::Ssl(None)),
+        }
+    }
+
+    /// Offers to resume session in the handshake. Whether the server accepted
+    /// it is reported by ConnectionInfo::session_resumed.
+    pub fn set_session(&mut self, session: &Session) -> Result<()> {
+        match unsafe { ffi::SSL_set_session(self.conn.ssl, session.as_ptr()) } {
+            1 => Ok(()),
+            _ => Err(Error::Ssl(None)),
         }
     }
+
+    /// Returns the session established by the handshake, if any. In TLS 1.3
+    /// it can't be resumed; use ContextBuilder::set_new_session_callback.
+    pub fn session(&self) -> Option<Session> {
+        let session = unsafe { ffi::SSL_get1_session(self.conn.ssl) };
+        if session.is_null() {
+            return None;
+        }
+        Some(unsafe { Session::from_ptr(session) })
+    }
+
+    /// Configures ssl to advertise name in the server_name extension (RFC 3546).
+    pub fn set_hostname(&mut self, hostname: &str) -> Result<()> {
+        let cstr = CString::new(hostname).unwrap();
+        let ret_code = unsafe { ffi::SSL_set_tlsext_host_name(self.conn.ssl, cstr.as_ptr()) };
+        match ret_code {
+            1 => Ok(()),
+            n => Err(self.conn.get_error(n)),
+        }
+    }
+
+    /// Sets the P-256 key used to send a TLS Channel ID, enabling the
+    /// extension on this connection.
+    pub fn set_channel_id_key(&mut self, key: &PrivateKey) -> Result<()> {
+        match unsafe { ffi::SSL_set1_tls_channel_id(self.conn.ssl, key.as_ptr()) } {
+            1 => Ok(()),
+            _ => Err(Error::Ssl(None)),
+        }
+    }
+
+    /// Passes handshake data received from the peer at level to the
+    /// connection. It is processed by the next call to handshake, or to
+    /// process_quic_post_handshake once the handshake has completed.
+    #[cfg(feature = "quic")]
+    pub fn provide_quic_data(&mut self, level: EncryptionLevel, data: &[u8]) -> Result<()> {
+        self.conn.provide_quic_data(level, data)
+    }
+
+    /// Processes data provided after the handshake, e.g. session tickets.
+    #[cfg(feature = "quic")]
+    pub fn process_quic_post_handshake(&mut self) -> Result<()> {
+        self.conn.process_quic_post_handshake()
+    }
+
+    /// Returns the level at which handshake data is currently expected.
+    #[cfg(feature = "quic")]
+    pub fn quic_read_level(&self) -> EncryptionLevel {
+        self.conn.quic_read_level()
+    }
+
+    /// Returns the level at which handshake data is currently sent.
+    #[cfg(feature = "quic")]
+    pub fn quic_write_level(&self) -> EncryptionLevel {
+        self.conn.quic_write_level()
+    }
+
+    /// Sets the QUIC transport parameters sent to the peer.
+    #[cfg(feature = "quic")]
+    pub fn set_quic_transport_params(&mut self, params: &[u8]) -> Result<()> {
+        self.conn.set_quic_transport_params(params)
+    }
+
+    /// Returns the QUIC transport parameters sent by the peer, if any.
+    #[cfg(feature = "quic")]
+    pub fn peer_quic_transport_params(&self) -> Option<&[u8]> {
+        self.conn.peer_quic_transport_params()
+    }
+
+    /// Returns a snapshot of the parameters negotiated by the handshake.
+    pub fn info(&self) -> ConnectionInfo {
+        self.conn.info()
+    }
+
+    /// Returns the server's leaf certificate, or None if the handshake has not
+    /// completed.
+    pub fn peer_certificate(&self) -> Option<X509> {
+        self.conn.peer_certificate()
+    }
+
+    /// Returns the certificate chain sent by the server, starting with the
+    /// leaf certificate.
+    pub fn peer_cert_chain(&self) -> Vec<X509> {
+        self.conn.peer_cert_chain()
+    }
+
+    /// Returns the result of verifying the server's certificate.
+    pub fn verify_result(&self) -> result::Result<(), VerifyError> {
+        self.conn.verify_result()
+    }
+
+    /// Derives len bytes of keying material from the session as specified in
+    /// RFC 5705, or by the TLS 1.3 exporter. context, if given, is mixed in;
+    /// in TLS 1.2 no context differs from an empty one.
+    /// It fails if the handshake has not completed.
+    pub fn export_keying_material(&self,
+                                  label: &str,
+                                  context: Option<&[u8]>,
+                                  len: usize)
+                                  -> Result<Vec<u8>> {
+        self.conn.export_keying_material(label, context, len)
+    }
+
+    /// Returns the tls-unique channel binding (RFC 5929), or None if it is
+    /// unavailable. It is not defined for TLS 1.3 and is only secure in TLS 1.2
+    /// with the extended master secret extension.
+    pub fn tls_unique(&self) -> Option<Vec<u8>> {
+        self.conn.tls_unique()
+    }
+
+    /// Returns the tls-exporter channel binding (RFC 9266), the 32-byte
+    /// exporter output for the label "EXPORTER-Channel-Binding".
+    pub fn tls_exporter(&self) -> Result<Vec<u8>> {
+        self.conn.export_keying_material("EXPORTER-Channel-Binding", None, 32)
+    }
+
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.conn.read(buf)
+    }
+
+    pub fn pending(&mut self) -> usize {
+        self.conn.pending()
+    }
+
+    pub fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.conn.write(buf)
+    }
+}
+
+/// A TLS server connection. Like Client, it is Send but not Sync.
+///
+/// ```compile_fail
+/// fn assert_sync<T: Sync>() {}
+/// assert_sync::<boringssl::Server>();
+/// ```
+pub struct Server {
+    conn: Connection,
+}
+
+impl Server {
+    fn new(ctx: &Context) -> Result<Server> {
+        let conn = try!(Connection::new(ctx));
+
+        // configure as server
+        unsafe { ffi::SSL_set_accept_state(conn.ssl) };
+        Ok(Server { conn: conn })
+    }
+
+    pub fn new_socket(ctx: &Context, fd: RawFd) -> Result<Server> {
+        let mut server = try!(Server::new(ctx));
+        try!(server.conn.set_fd(fd));
+        Ok(server)
+    }
+
+    /// Creates a server that reads from and writes to bio, which the connection
+    /// takes ownership of. It is freed together with the connection unless
+    /// other handles to it remain.
+    ///
+    /// # Safety
+    ///
+    /// BIOs are not thread-safe. If handles to bio, or to the other end of
+    /// a BIO pair, are kept, the connection must not be moved to another
+    /// thread while they are in use.
+    pub unsafe fn new_bio(ctx: &Context, bio: Bio) -> Result<Server> {
+        let mut server = try!(Server::new(ctx));
+        server.conn.set_bio(bio);
+        Ok(server)
+    }
+
+    /// Creates a server for QUIC. The handshake has no transport; it is driven
+    /// through method and provide_quic_data instead. QUIC requires TLS 1.3
+    /// and ALPN.
+    #[cfg(feature = "quic")]
+    pub fn new_quic<M>(ctx: &Context, method: M) -> Result<Server>
+        where M: QuicMethod + 'static
+    {
+        let mut server = try!(Server::new(ctx));
+        try!(server.conn.set_quic_method(method));
+        Ok(server)
+    }
+
+    /// If <0 is returned, it must be called again when the underlying stream is
+    /// ready to contiue the handshake.
+    pub fn handshake(&mut self) -> Result<()> {
+        self.conn.handshake()
+    }
+
+    /// Returns the most recent alert sent or received on the connection.
+    pub fn last_alert(&self) -> Option<Alert> {
+        self.conn.state.last_alert
+    }
+
+    /// Sets the path MTU for DTLS, excluding IP and UDP headers.
+    pub fn set_mtu(&mut self, mtu: u32) -> Result<()> {
+        self.conn.set_mtu(mtu)
+    }
+
+    /// Returns the time until the current DTLS handshake timeout expires, or
+    /// None if there is none. The caller should wait for the transport to
+    /// become readable no longer than that, then call handle_dtls_timeout.
+    pub fn dtls_timeout(&self) -> Option<Duration> {
+        self.conn.dtls_timeout()
+    }
+
+    /// Retransmits the previous DTLS handshake flight if the timeout expired.
+    /// It returns whether it did. It fails if too many timeouts expired
+    /// without progress.
+    pub fn handle_dtls_timeout(&mut self) -> Result<bool> {
+        self.conn.handle_dtls_timeout()
+    }
+
+    /// Offloads the private key operations of this connection to method,
+    /// overriding the context's private key or method.
+    #[cfg(boringssl_private_key_method)]
+    pub fn set_private_key_method<M>(&mut self, method: M)
+        where M: PrivateKeyMethod + 'static
+    {
+        self.conn.set_private_key_method(method)
+    }
+
+    /// Returns the hostname the client requested in the server_name extension,
+    /// or None if it sent none.
+    pub fn server_name(&self) -> Option<&str> {
+        unsafe { server_name::get_servername(self.conn.ssl) }
+    }
+
+    /// Returns the client's verified TLS Channel ID, its P-256 public key as
+    /// big-endian x and y coordinates, or None if the client sent none.
+    /// Channel IDs must be enabled with ContextBuilder::enable_tls_channel_id.
+    pub fn channel_id(&self) -> Option<[u8; 64]> {
+        let mut id = [0u8; 64];
+        let len = unsafe { ffi::SSL_get_tls_channel_id(self.conn.ssl, id.as_mut_ptr(), id.len()) };
+        match len {
+            64 => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Passes handshake data received from the peer at level to the
+    /// connection. It is processed by the next call to handshake, or to
+    /// process_quic_post_handshake once the handshake has completed.
+    #[cfg(feature = "quic")]
+    pub fn provide_quic_data(&mut self, level: EncryptionLevel, data: &[u8]) -> Result<()> {
+        self.conn.provide_quic_data(level, data)
+    }
+
+    /// Processes data provided after the handshake, e.g. session tickets.
+    #[cfg(feature = "quic")]
+    pub fn process_quic_post_handshake(&mut self) -> Result<()> {
+        self.conn.process_quic_post_handshake()
+    }
+
+    /// Returns the level at which handshake data is currently expected.
+    #[cfg(feature = "quic")]
+    pub fn quic_read_level(&self) -> EncryptionLevel {
+        self.conn.quic_read_level()
+    }
+
+    /// Returns the level at which handshake data is currently sent.
+    #[cfg(feature = "quic")]
+    pub fn quic_write_level(&self) -> EncryptionLevel {
+        self.conn.quic_write_level()
+    }
+
+    /// Sets the QUIC transport parameters sent to the peer.
+    #[cfg(feature = "quic")]
+    pub fn set_quic_transport_params(&mut self, params: &[u8]) -> Result<()> {
+        self.conn.set_quic_transport_params(params)
+    }
+
+    /// Returns the QUIC transport parameters sent by the peer, if any.
+    #[cfg(feature = "quic")]
+    pub fn peer_quic_transport_params(&self) -> Option<&[u8]> {
+        self.conn.peer_quic_transport_params()
+    }
+
+    /// Returns a snapshot of the parameters negotiated by the handshake.
+    pub fn info(&self) -> ConnectionInfo {
+        self.conn.info()
+    }
+
+    /// Returns the client's leaf certificate, or None if the client did not
+    /// authenticate.
+    pub fn peer_certificate(&self) -> Option<X509> {
+        self.conn.peer_certificate()
+    }
+
+    /// Returns the certificate chain sent by the client. Unlike on the client,
+    /// the chain does not include the leaf certificate.
+    pub fn peer_cert_chain(&self) -> Vec<X509> {
+        self.conn.peer_cert_chain()
+    }
+
+    /// Returns the result of verifying the client's certificate.
+    pub fn verify_result(&self) -> result::Result<(), VerifyError> {
+        self.conn.verify_result()
+    }
+
+    /// Derives len bytes of keying material from the session as specified in
+    /// RFC 5705, or by the TLS 1.3 exporter. context, if given, is mixed in;
+    /// in TLS 1.2 no context differs from an empty one.
+    /// It fails if the handshake has not completed.
+    pub fn export_keying_material(&self,
+                                  label: &str,
+                                  context: Option<&[u8]>,
+                                  len: usize)
+                                  -> Result<Vec<u8>> {
+        self.conn.export_keying_material(label, context, len)
+    }
+
+    /// Returns the tls-unique channel binding (RFC 5929), or None if it is
+    /// unavailable. It is not defined for TLS 1.3 and is only secure in TLS 1.2
+    /// with the extended master secret extension.
+    pub fn tls_unique(&self) -> Option<Vec<u8>> {
+        self.conn.tls_unique()
+    }
+
+    /// Returns the tls-exporter channel binding (RFC 9266), the 32-byte
+    /// exporter output for the label "EXPORTER-Channel-Binding".
+    pub fn tls_exporter(&self) -> Result<Vec<u8>> {
+        self.conn.export_keying_material("EXPORTER-Channel-Binding", None, 32)
+    }
+
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.conn.read(buf)
+    }
+
+    pub fn pending(&mut self) -> usize {
+        self.conn.pending()
+    }
+
+    pub fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.conn.write(buf)
+    }
 }
 
 /// SslError is a packed representation of an internal error in the SSL library.
@@ -0,0 +1,110 @@
+use std::mem;
+use std::os::raw::c_long;
+use std::ptr;
+
+use bio::Bio;
+use ffi;
+use {Error, Result};
+
+/// A private key, used to authenticate a server or client.
+/// The underlying EVP_PKEY is reference-counted, so cloning is cheap.
+pub struct PrivateKey {
+    pkey: *mut ffi::EVP_PKEY,
+}
+
+// The EVP_PKEY is immutable once parsed and reference-counted atomically.
+unsafe impl Send for PrivateKey {}
+unsafe impl Sync for PrivateKey {}
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        unsafe { ffi::EVP_PKEY_free(self.pkey) }
+    }
+}
+
+impl Clone for PrivateKey {
+    fn clone(&self) -> PrivateKey {
+        unsafe { ffi::EVP_PKEY_up_ref(self.pkey) };
+        PrivateKey { pkey: self.pkey }
+    }
+}
+
+impl PrivateKey {
+    pub(crate) fn as_ptr(&self) -> *mut ffi::EVP_PKEY {
+        self.pkey
+    }
+
+    /// Releases ownership of the reference to the caller.
+    pub(crate) fn into_ptr(self) -> *mut ffi::EVP_PKEY {
+        let pkey = self.pkey;
+        mem::forget(self);
+        pkey
+    }
+
+    /// Parses the first PEM-encoded private key in pem.
+    pub fn from_pem(pem: &[u8]) -> Result<PrivateKey> {
+        let bio = try!(Bio::from_slice(pem));
+        let pkey = unsafe {
+            ffi::PEM_read_bio_PrivateKey(bio.as_ptr(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut())
+        };
+        if pkey.is_null() {
+            return Err(Error::Ssl(None));
+        }
+        Ok(PrivateKey { pkey: pkey })
+    }
+
+    /// Signs input with the TLS signature algorithm sigalg (e.g. 0x0804 for
+    /// rsa_pss_rsae_sha256), as done during the handshake. This is useful to
+    /// implement a PrivateKeyMethod over a key held elsewhere.
+    #[cfg(boringssl_private_key_method)]
+    pub fn sign(&self, sigalg: u16, input: &[u8]) -> Result<Vec<u8>> {
+        unsafe {
+            let ctx = ffi::EVP_MD_CTX_new();
+            if ctx.is_null() {
+                return Err(Error::AllocationFailed);
+            }
+            let ret = sign(ctx, self.pkey, sigalg, input);
+            ffi::EVP_MD_CTX_free(ctx);
+            ret
+        }
+    }
+
+    /// Parses a DER-encoded PKCS#8, PKCS#1 or SEC1 private key.
+    pub fn from_der(der: &[u8]) -> Result<PrivateKey> {
+        let mut inp = der.as_ptr();
+        let pkey =
+            unsafe { ffi::d2i_AutoPrivateKey(ptr::null_mut(), &mut inp, der.len() as c_long) };
+        if pkey.is_null() {
+            return Err(Error::Ssl(None));
+        }
+        Ok(PrivateKey { pkey: pkey })
+    }
+}
+
+#[cfg(boringssl_private_key_method)]
+unsafe fn sign(ctx: *mut ffi::EVP_MD_CTX,
+               pkey: *mut ffi::EVP_PKEY,
+               sigalg: u16,
+               input: &[u8])
+               -> Result<Vec<u8>> {
+    let md = ffi::SSL_get_signature_algorithm_digest(sigalg);
+    let mut pctx = ptr::null_mut();
+    if ffi::EVP_DigestSignInit(ctx, &mut pctx, md, ptr::null_mut(), pkey) != 1 {
+        return Err(Error::Ssl(None));
+    }
+    if ffi::SSL_is_signature_algorithm_rsa_pss(sigalg) == 1 &&
+       (ffi::EVP_PKEY_CTX_set_rsa_padding(pctx, ffi::RSA_PKCS1_PSS_PADDING) != 1 ||
+        ffi::EVP_PKEY_CTX_set_rsa_pss_saltlen(pctx, -1 /* digest length */) != 1) {
+        return Err(Error::Ssl(None));
+    }
+    let mut len = 0;
+    if ffi::EVP_DigestSign(ctx, ptr::null_mut(), &mut len, input.as_ptr(), input.len()) != 1 {
+        return Err(Error::Ssl(None));
+    }
+    let mut sig = vec![0; len];
+    if ffi::EVP_DigestSign(ctx, sig.as_mut_ptr(), &mut len, input.as_ptr(), input.len()) != 1 {
+        return Err(Error::Ssl(None));
+    }
+    sig.truncate(len);
+    Ok(sig)
+}
@@ -0,0 +1,120 @@
+use std::os::raw::c_int;
+use std::slice;
+
+use ffi;
+use {conn_state, ctx_state, CtxState};
+
+pub type Factory = dyn Fn() -> Box<dyn PrivateKeyMethod> + Send + Sync;
+
+/// The result of a private key operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivateKeyResult {
+    /// The operation completed and wrote the given number of bytes to out.
+    Success(usize),
+
+    /// The operation is pending. The handshake fails with
+    /// Error::WantPrivateKeyOperation and PrivateKeyMethod::complete is called
+    /// when it is retried.
+    Retry,
+
+    /// The operation failed. The handshake is aborted.
+    Failure,
+}
+
+/// PrivateKeyMethod offloads the private key operations of the handshake,
+/// e.g. to a separate signing service, instead of using a PrivateKey.
+///
+/// Each connection gets its own instance, so a pending operation can be kept
+/// in self between a Retry and the call to complete. It moves with the
+/// connection between threads and so must be Send.
+pub trait PrivateKeyMethod: Send {
+    /// Signs input with the TLS signature algorithm sigalg and writes the
+    /// signature to out.
+    fn sign(&mut self, sigalg: u16, input: &[u8], out: &mut [u8]) -> PrivateKeyResult;
+
+    /// Decrypts input with an RSA private key and writes the plaintext to out.
+    /// This is only used for TLS 1.2 RSA key exchange.
+    fn decrypt(&mut self, input: &[u8], out: &mut [u8]) -> PrivateKeyResult;
+
+    /// Completes the pending sign or decrypt operation, writing its output to
+    /// out. It is called each time the handshake is retried after an operation
+    /// returned Retry.
+    fn complete(&mut self, out: &mut [u8]) -> PrivateKeyResult;
+}
+
+pub static METHOD: ffi::SSL_PRIVATE_KEY_METHOD = ffi::SSL_PRIVATE_KEY_METHOD {
+    sign: Some(sign),
+    decrypt: Some(decrypt),
+    complete: Some(complete),
+};
+
+/// Runs op with the PrivateKeyMethod of ssl, instantiating it from the
+/// SSL_CTX if the connection has none of its own yet.
+fn with_method<F>(ssl: *mut ffi::SSL, out_len: *mut usize, op: F) -> c_int
+    where F: FnOnce(&mut dyn PrivateKeyMethod) -> PrivateKeyResult
+{
+    let state = match unsafe { conn_state(ssl) } {
+        Some(state) => state,
+        None => return ffi::ssl_private_key_failure,
+    };
+    if state.private_key_method.is_none() {
+        if let Some(&CtxState { private_key_method: Some(ref factory), .. }) =
+               unsafe { ctx_state(ssl) } {
+            state.private_key_method = Some(factory());
+        }
+    }
+    let method = match state.private_key_method {
+        Some(ref mut method) => method,
+        None => return ffi::ssl_private_key_failure,
+    };
+    match op(&mut **method) {
+        PrivateKeyResult::Success(len) => {
+            unsafe { *out_len = len };
+            ffi::ssl_private_key_success
+        }
+        PrivateKeyResult::Retry => ffi::ssl_private_key_retry,
+        PrivateKeyResult::Failure => ffi::ssl_private_key_failure,
+    }
+}
+
+extern "C" fn sign(ssl: *mut ffi::SSL,
+                   out: *mut u8,
+                   out_len: *mut usize,
+                   max_out: usize,
+                   sigalg: u16,
+                   in_: *const u8,
+                   in_len: usize)
+                   -> c_int {
+    let (input, out) =
+        unsafe { (slice::from_raw_parts(in_, in_len), slice::from_raw_parts_mut(out, max_out)) };
+    with_method(ssl, out_len, |method| checked(method.sign(sigalg, input, out), max_out))
+}
+
+extern "C" fn decrypt(ssl: *mut ffi::SSL,
+                      out: *mut u8,
+                      out_len: *mut usize,
+                      max_out: usize,
+                      in_: *const u8,
+                      in_len: usize)
+                      -> c_int {
+    let (input, out) =
+        unsafe { (slice::from_raw_parts(in_, in_len), slice::from_raw_parts_mut(out, max_out)) };
+    with_method(ssl, out_len, |method| checked(method.decrypt(input, out), max_out))
+}
+
+extern "C" fn complete(ssl: *mut ffi::SSL,
+                       out: *mut u8,
+                       out_len: *mut usize,
+                       max_out: usize)
+                       -> c_int {
+    let out = unsafe { slice::from_raw_parts_mut(out, max_out) };
+    with_method(ssl, out_len, |method| checked(method.complete(out), max_out))
+}
+
+/// Turns a Success claiming more than max_out bytes into a Failure.
+fn checked(result: PrivateKeyResult, max_out: usize) -> PrivateKeyResult {
+    match result {
+        PrivateKeyResult::Success(len) if len > max_out => PrivateKeyResult::Failure,
+        result => result,
+    }
+}
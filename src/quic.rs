@@ -0,0 +1,136 @@
+use std::os::raw::c_int;
+use std::slice;
+
+use ffi;
+use {conn_state, AlertDescription, ConnState};
+
+/// A QUIC encryption level, each of which has its own keys and handshake
+/// data stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EncryptionLevel {
+    Initial,
+    EarlyData,
+    Handshake,
+    Application,
+}
+
+impl EncryptionLevel {
+    pub(crate) fn from_raw(level: ffi::ssl_encryption_level_t) -> EncryptionLevel {
+        match level {
+            ffi::ssl_encryption_initial => EncryptionLevel::Initial,
+            ffi::ssl_encryption_early_data => EncryptionLevel::EarlyData,
+            ffi::ssl_encryption_handshake => EncryptionLevel::Handshake,
+            _ => EncryptionLevel::Application,
+        }
+    }
+
+    pub(crate) fn to_raw(self) -> ffi::ssl_encryption_level_t {
+        match self {
+            EncryptionLevel::Initial => ffi::ssl_encryption_initial,
+            EncryptionLevel::EarlyData => ffi::ssl_encryption_early_data,
+            EncryptionLevel::Handshake => ffi::ssl_encryption_handshake,
+            EncryptionLevel::Application => ffi::ssl_encryption_application,
+        }
+    }
+}
+
+/// QuicMethod connects a connection's TLS 1.3 handshake to a QUIC transport.
+/// Instead of writing records, the handshake passes its messages and traffic
+/// secrets to these hooks; data received from the peer is passed back with
+/// provide_quic_data.
+///
+/// Each hook returns false on error, which aborts the handshake.
+pub trait QuicMethod: Send {
+    /// Installs the secret used to decrypt packets received at level.
+    /// cipher_suite is the IANA ID of the negotiated TLS 1.3 cipher suite.
+    fn set_read_secret(&mut self, level: EncryptionLevel, cipher_suite: u16, secret: &[u8])
+                       -> bool;
+
+    /// Installs the secret used to encrypt packets sent at level.
+    fn set_write_secret(&mut self, level: EncryptionLevel, cipher_suite: u16, secret: &[u8])
+                        -> bool;
+
+    /// Appends data to the handshake data to be sent at level.
+    fn add_handshake_data(&mut self, level: EncryptionLevel, data: &[u8]) -> bool;
+
+    /// Sends the handshake data added so far.
+    fn flush_flight(&mut self) -> bool;
+
+    /// Sends a fatal alert at level, e.g. as a QUIC CONNECTION_CLOSE frame.
+    fn send_alert(&mut self, level: EncryptionLevel, alert: AlertDescription) -> bool;
+}
+
+pub static METHOD: ffi::SSL_QUIC_METHOD = ffi::SSL_QUIC_METHOD {
+    set_read_secret: Some(set_read_secret),
+    set_write_secret: Some(set_write_secret),
+    add_handshake_data: Some(add_handshake_data),
+    flush_flight: Some(flush_flight),
+    send_alert: Some(send_alert),
+};
+
+/// Runs op with the QuicMethod of ssl.
+fn with_method<F>(ssl: *mut ffi::SSL, op: F) -> c_int
+    where F: FnOnce(&mut dyn QuicMethod) -> bool
+{
+    let method = match unsafe { conn_state(ssl) } {
+        Some(&mut ConnState { quic_method: Some(ref mut method), .. }) => method,
+        _ => return 0,
+    };
+    op(&mut **method) as c_int
+}
+
+unsafe fn to_slice<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if data.is_null() || len == 0 {
+        return &[];
+    }
+    slice::from_raw_parts(data, len)
+}
+
+extern "C" fn set_read_secret(ssl: *mut ffi::SSL,
+                              level: ffi::ssl_encryption_level_t,
+                              cipher: *const ffi::SSL_CIPHER,
+                              secret: *const u8,
+                              secret_len: usize)
+                              -> c_int {
+    let (cipher_suite, secret) =
+        unsafe { (ffi::SSL_CIPHER_get_protocol_id(cipher), to_slice(secret, secret_len)) };
+    with_method(ssl, |method| {
+        method.set_read_secret(EncryptionLevel::from_raw(level), cipher_suite, secret)
+    })
+}
+
+extern "C" fn set_write_secret(ssl: *mut ffi::SSL,
+                               level: ffi::ssl_encryption_level_t,
+                               cipher: *const ffi::SSL_CIPHER,
+                               secret: *const u8,
+                               secret_len: usize)
+                               -> c_int {
+    let (cipher_suite, secret) =
+        unsafe { (ffi::SSL_CIPHER_get_protocol_id(cipher), to_slice(secret, secret_len)) };
+    with_method(ssl, |method| {
+        method.set_write_secret(EncryptionLevel::from_raw(level), cipher_suite, secret)
+    })
+}
+
+extern "C" fn add_handshake_data(ssl: *mut ffi::SSL,
+                                 level: ffi::ssl_encryption_level_t,
+                                 data: *const u8,
+                                 len: usize)
+                                 -> c_int {
+    let data = unsafe { to_slice(data, len) };
+    with_method(ssl,
+                |method| method.add_handshake_data(EncryptionLevel::from_raw(level), data))
+}
+
+extern "C" fn flush_flight(ssl: *mut ffi::SSL) -> c_int {
+    with_method(ssl, |method| method.flush_flight())
+}
+
+extern "C" fn send_alert(ssl: *mut ffi::SSL,
+                         level: ffi::ssl_encryption_level_t,
+                         alert: u8)
+                         -> c_int {
+    with_method(ssl, |method| {
+        method.send_alert(EncryptionLevel::from_raw(level), AlertDescription::from_u8(alert))
+    })
+}
@@ -0,0 +1,69 @@
+use std::ffi::CStr;
+use std::os::raw::{c_int, c_void};
+use std::str;
+
+use ffi;
+use {Context, CtxState, Error, Result};
+
+pub type Callback = dyn Fn(&mut ServerNameRequest) -> ServerNameAction + Send + Sync;
+
+/// The result of the server name callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerNameAction {
+    /// Continue the handshake.
+    Accept,
+
+    /// Abort the handshake with a fatal unrecognized_name alert.
+    Reject,
+}
+
+/// ServerNameRequest is passed to the server name callback.
+/// It gives access to the hostname requested by the client and allows the
+/// callback to switch the connection to a different Context.
+pub struct ServerNameRequest {
+    ssl: *mut ffi::SSL,
+}
+
+impl ServerNameRequest {
+    /// Returns the hostname sent by the client in the server_name extension,
+    /// or None if it sent none.
+    pub fn server_name(&self) -> Option<&str> {
+        unsafe { get_servername(self.ssl) }
+    }
+
+    /// Switches the connection to ctx. The certificate and private key of ctx
+    /// are used for the remainder of the handshake.
+    /// The connection holds its own reference to ctx.
+    pub fn set_context(&mut self, ctx: &Context) -> Result<()> {
+        let ret = unsafe { ffi::SSL_set_SSL_CTX(self.ssl, ctx.ctx) };
+        if ret.is_null() {
+            return Err(Error::Ssl(None));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the server_name requested on ssl, if any and valid UTF-8.
+pub unsafe fn get_servername<'a>(ssl: *const ffi::SSL) -> Option<&'a str> {
+    let name = ffi::SSL_get_servername(ssl, ffi::TLSEXT_NAMETYPE_host_name);
+    if name.is_null() {
+        return None;
+    }
+    str::from_utf8(CStr::from_ptr(name).to_bytes()).ok()
+}
+
+pub extern "C" fn callback(ssl: *mut ffi::SSL, out_alert: *mut c_int, arg: *mut c_void) -> c_int {
+    let state = unsafe { &*(arg as *const CtxState) };
+    let callback = match state.server_name_callback {
+        Some(ref callback) => callback,
+        None => return ffi::SSL_TLSEXT_ERR_OK,
+    };
+    let mut req = ServerNameRequest { ssl: ssl };
+    match callback(&mut req) {
+        ServerNameAction::Accept => ffi::SSL_TLSEXT_ERR_OK,
+        ServerNameAction::Reject => {
+            unsafe { *out_alert = ffi::SSL_AD_UNRECOGNIZED_NAME as c_int };
+            ffi::SSL_TLSEXT_ERR_ALERT_FATAL
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::os::raw::c_int;
+
+use ffi;
+use {ctx_state, CtxState};
+
+pub type Callback = dyn Fn(Session) + Send + Sync;
+
+/// A TLS session, which a client can offer to resume in a later handshake
+/// with the same server, skipping the certificate exchange.
+/// The underlying SSL_SESSION is reference-counted, so cloning is cheap.
+pub struct Session {
+    session: *mut ffi::SSL_SESSION,
+}
+
+// The SSL_SESSION is immutable once established and reference-counted
+// atomically.
+unsafe impl Send for Session {}
+unsafe impl Sync for Session {}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        unsafe { ffi::SSL_SESSION_free(self.session) }
+    }
+}
+
+impl Clone for Session {
+    fn clone(&self) -> Session {
+        unsafe { ffi::SSL_SESSION_up_ref(self.session) };
+        Session { session: self.session }
+    }
+}
+
+impl Session {
+    /// Takes ownership of a reference to session, which must not be NULL.
+    pub(crate) unsafe fn from_ptr(session: *mut ffi::SSL_SESSION) -> Session {
+        Session { session: session }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut ffi::SSL_SESSION {
+        self.session
+    }
+}
+
+/// Passes a new client session to the callback of the context ssl belongs
+/// to, which takes ownership of it.
+pub extern "C" fn new_session_callback(ssl: *mut ffi::SSL,
+                                      session: *mut ffi::SSL_SESSION)
+                                      -> c_int {
+    let callback = match unsafe { ctx_state(ssl) } {
+        Some(&CtxState { new_session_callback: Some(ref callback), .. }) => callback,
+        _ => return 0,
+    };
+    callback(unsafe { Session::from_ptr(session) });
+    1
+}
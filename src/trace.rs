@@ -0,0 +1,211 @@
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::{c_int, c_void};
+use std::slice;
+use std::str;
+
+use ffi;
+use {ctx_state, Alert, CtxState};
+
+pub type InfoCallback = dyn Fn(&InfoEvent) + Send + Sync;
+pub type MsgCallback = dyn Fn(&Message) + Send + Sync;
+
+/// An event reported to the info callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfoEvent<'a> {
+    /// A handshake started.
+    HandshakeStart,
+
+    /// A handshake completed successfully.
+    HandshakeDone,
+
+    /// An alert was sent or received.
+    Alert(Alert),
+
+    /// The handshake state machine advanced to the described state.
+    StateChange(&'a str),
+
+    /// The handshake function returned the given value in the described
+    /// state, e.g. because it needs to wait for the transport or failed.
+    Exit(&'a str, i32),
+}
+
+impl<'a> InfoEvent<'a> {
+    /// Decodes the arguments of the info callback.
+    pub(crate) unsafe fn from_info(ssl: *const ffi::SSL,
+                                   type_: c_int,
+                                   value: c_int)
+                                   -> Option<InfoEvent<'a>> {
+        if let Some(alert) = Alert::from_info(type_, value) {
+            return Some(InfoEvent::Alert(alert));
+        }
+        match type_ {
+            ffi::SSL_CB_HANDSHAKE_START => Some(InfoEvent::HandshakeStart),
+            ffi::SSL_CB_HANDSHAKE_DONE => Some(InfoEvent::HandshakeDone),
+            t if t & ffi::SSL_CB_LOOP != 0 => Some(InfoEvent::StateChange(state_string(ssl))),
+            t if t & ffi::SSL_CB_EXIT != 0 => Some(InfoEvent::Exit(state_string(ssl), value)),
+            _ => None,
+        }
+    }
+}
+
+unsafe fn state_string<'a>(ssl: *const ffi::SSL) -> &'a str {
+    str::from_utf8(CStr::from_ptr(ffi::SSL_state_string_long(ssl)).to_bytes()).unwrap_or("")
+}
+
+/// Runs the info callback of the context ssl belongs to, if any.
+pub fn info(ssl: *const ffi::SSL, type_: c_int, value: c_int) {
+    let callback = match unsafe { ctx_state(ssl) } {
+        Some(&CtxState { info_callback: Some(ref callback), .. }) => callback,
+        _ => return,
+    };
+    if let Some(event) = unsafe { InfoEvent::from_info(ssl, type_, value) } {
+        callback(&event);
+    }
+}
+
+/// Whether a message was sent or received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// The record type of a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentType {
+    ChangeCipherSpec,
+    Alert,
+    Handshake,
+    ApplicationData,
+
+    /// A record header rather than a message.
+    Header,
+
+    Unknown(i32),
+}
+
+/// A protocol message, as passed to the message callback.
+/// Its Display implementation prints it in the style of `openssl s_client
+/// -msg`: a summary line followed by a hex dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Message<'a> {
+    pub direction: Direction,
+    pub version: u16,
+    pub content_type: ContentType,
+    pub data: &'a [u8],
+}
+
+impl<'a> Message<'a> {
+    /// Returns the handshake message type for handshake messages.
+    pub fn handshake_type(&self) -> Option<u8> {
+        match self.content_type {
+            ContentType::Handshake => self.data.first().cloned(),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> fmt::Display for Message<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let dir = match self.direction {
+            Direction::Sent => ">>>",
+            Direction::Received => "<<<",
+        };
+        let version = match self.version {
+            0x0300 => "SSL 3.0",
+            0x0301 => "TLS 1.0",
+            0x0302 => "TLS 1.1",
+            0x0303 => "TLS 1.2",
+            0x0304 => "TLS 1.3",
+            0xfeff => "DTLS 1.0",
+            0xfefd => "DTLS 1.2",
+            _ => "???",
+        };
+        let content_type = match self.content_type {
+            ContentType::ChangeCipherSpec => "ChangeCipherSpec",
+            ContentType::Alert => "Alert",
+            ContentType::Handshake => "Handshake",
+            ContentType::ApplicationData => "ApplicationData",
+            ContentType::Header => "RecordHeader",
+            ContentType::Unknown(_) => "???",
+        };
+        try!(write!(f,
+                    "{} {}, {} [length {:04x}]",
+                    dir,
+                    version,
+                    content_type,
+                    self.data.len()));
+        if let Some(t) = self.handshake_type() {
+            try!(write!(f, ", {}", handshake_type_name(t)));
+        }
+        for line in self.data.chunks(16) {
+            try!(f.write_str("\n   "));
+            for b in line {
+                try!(write!(f, " {:02x}", b));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn handshake_type_name(t: u8) -> &'static str {
+    match t {
+        0 => "HelloRequest",
+        1 => "ClientHello",
+        2 => "ServerHello",
+        3 => "HelloVerifyRequest",
+        4 => "NewSessionTicket",
+        5 => "EndOfEarlyData",
+        8 => "EncryptedExtensions",
+        11 => "Certificate",
+        12 => "ServerKeyExchange",
+        13 => "CertificateRequest",
+        14 => "ServerHelloDone",
+        15 => "CertificateVerify",
+        16 => "ClientKeyExchange",
+        20 => "Finished",
+        22 => "CertificateStatus",
+        24 => "KeyUpdate",
+        67 => "NextProto",
+        203 => "ChannelID",
+        254 => "MessageHash",
+        _ => "???",
+    }
+}
+
+pub extern "C" fn msg_callback(is_write: c_int,
+                               version: c_int,
+                               content_type: c_int,
+                               buf: *const c_void,
+                               len: usize,
+                               ssl: *mut ffi::SSL,
+                               _arg: *mut c_void) {
+    let callback = match unsafe { ctx_state(ssl) } {
+        Some(&CtxState { msg_callback: Some(ref callback), .. }) => callback,
+        _ => return,
+    };
+    let data = if buf.is_null() || len == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(buf as *const u8, len) }
+    };
+    let content_type = match content_type {
+        ffi::SSL3_RT_CHANGE_CIPHER_SPEC => ContentType::ChangeCipherSpec,
+        ffi::SSL3_RT_ALERT => ContentType::Alert,
+        ffi::SSL3_RT_HANDSHAKE => ContentType::Handshake,
+        ffi::SSL3_RT_APPLICATION_DATA => ContentType::ApplicationData,
+        ffi::SSL3_RT_HEADER => ContentType::Header,
+        n => ContentType::Unknown(n),
+    };
+    callback(&Message {
+        direction: if is_write != 0 {
+            Direction::Sent
+        } else {
+            Direction::Received
+        },
+        version: version as u16,
+        content_type: content_type,
+        data: data,
+    });
+}
@@ -0,0 +1,663 @@
+use std::ffi::CStr;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::os::raw::{c_long, c_uint};
+use std::ptr;
+use std::slice;
+use std::str;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bio::Bio;
+use ffi;
+use {Error, Result};
+
+/// The algorithm of a certificate's public key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAlgorithm {
+    Rsa,
+    Dsa,
+    Ec,
+    Ed25519,
+
+    /// Any other algorithm, identified by its NID.
+    Unknown(i32),
+}
+
+/// An entry of the subjectAltName extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubjectAltName {
+    Dns(String),
+    Email(String),
+    Uri(String),
+    IpAddress(IpAddr),
+}
+
+/// The keyUsage extension of a certificate (RFC 5280, section 4.2.1.3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyUsage(u32);
+
+impl KeyUsage {
+    pub const DIGITAL_SIGNATURE: KeyUsage = KeyUsage(ffi::KU_DIGITAL_SIGNATURE);
+    pub const NON_REPUDIATION: KeyUsage = KeyUsage(ffi::KU_NON_REPUDIATION);
+    pub const KEY_ENCIPHERMENT: KeyUsage = KeyUsage(ffi::KU_KEY_ENCIPHERMENT);
+    pub const DATA_ENCIPHERMENT: KeyUsage = KeyUsage(ffi::KU_DATA_ENCIPHERMENT);
+    pub const KEY_AGREEMENT: KeyUsage = KeyUsage(ffi::KU_KEY_AGREEMENT);
+    pub const KEY_CERT_SIGN: KeyUsage = KeyUsage(ffi::KU_KEY_CERT_SIGN);
+    pub const CRL_SIGN: KeyUsage = KeyUsage(ffi::KU_CRL_SIGN);
+    pub const ENCIPHER_ONLY: KeyUsage = KeyUsage(ffi::KU_ENCIPHER_ONLY);
+    pub const DECIPHER_ONLY: KeyUsage = KeyUsage(ffi::KU_DECIPHER_ONLY);
+
+    /// Returns true if all bits of other are set.
+    pub fn contains(&self, other: KeyUsage) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+}
+
+/// The extendedKeyUsage extension of a certificate (RFC 5280, section
+/// 4.2.1.12). Only the purposes known to BoringSSL are represented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtendedKeyUsage(u32);
+
+impl ExtendedKeyUsage {
+    pub const SERVER_AUTH: ExtendedKeyUsage = ExtendedKeyUsage(ffi::XKU_SSL_SERVER);
+    pub const CLIENT_AUTH: ExtendedKeyUsage = ExtendedKeyUsage(ffi::XKU_SSL_CLIENT);
+    pub const EMAIL_PROTECTION: ExtendedKeyUsage = ExtendedKeyUsage(ffi::XKU_SMIME);
+    pub const CODE_SIGNING: ExtendedKeyUsage = ExtendedKeyUsage(ffi::XKU_CODE_SIGN);
+    pub const OCSP_SIGNING: ExtendedKeyUsage = ExtendedKeyUsage(ffi::XKU_OCSP_SIGN);
+    pub const TIME_STAMPING: ExtendedKeyUsage = ExtendedKeyUsage(ffi::XKU_TIMESTAMP);
+    pub const ANY: ExtendedKeyUsage = ExtendedKeyUsage(ffi::XKU_ANYEKU);
+
+    /// Returns true if all bits of other are set.
+    pub fn contains(&self, other: ExtendedKeyUsage) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+}
+
+/// An X.509 certificate.
+/// The underlying X509 is reference-counted, so cloning is cheap.
+pub struct X509 {
+    x509: *mut ffi::X509,
+}
+
+// The X509 is immutable once parsed and reference-counted atomically.
+unsafe impl Send for X509 {}
+unsafe impl Sync for X509 {}
+
+impl Drop for X509 {
+    fn drop(&mut self) {
+        unsafe { ffi::X509_free(self.x509) }
+    }
+}
+
+impl Clone for X509 {
+    fn clone(&self) -> X509 {
+        unsafe { ffi::X509_up_ref(self.x509) };
+        X509 { x509: self.x509 }
+    }
+}
+
+impl fmt::Debug for X509 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("X509")
+            .field("subject", &self.subject_name())
+            .field("issuer", &self.issuer_name())
+            .finish()
+    }
+}
+
+impl X509 {
+    /// Takes ownership of a reference to x509.
+    pub(crate) unsafe fn from_ptr(x509: *mut ffi::X509) -> X509 {
+        X509 { x509: x509 }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut ffi::X509 {
+        self.x509
+    }
+
+    /// Parses the first PEM-encoded certificate in pem.
+    pub fn from_pem(pem: &[u8]) -> Result<X509> {
+        let bio = try!(Bio::from_slice(pem));
+        let x509 = unsafe {
+            ffi::PEM_read_bio_X509(bio.as_ptr(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut())
+        };
+        if x509.is_null() {
+            return Err(Error::Ssl(None));
+        }
+        Ok(X509 { x509: x509 })
+    }
+
+    /// Parses a DER-encoded certificate.
+    pub fn from_der(der: &[u8]) -> Result<X509> {
+        let mut inp = der.as_ptr();
+        let x509 = unsafe { ffi::d2i_X509(ptr::null_mut(), &mut inp, der.len() as c_long) };
+        if x509.is_null() {
+            return Err(Error::Ssl(None));
+        }
+        Ok(X509 { x509: x509 })
+    }
+
+    /// Returns the PEM encoding of the certificate.
+    pub fn to_pem(&self) -> Result<Vec<u8>> {
+        let bio = try!(Bio::new_mem());
+        if unsafe { ffi::PEM_write_bio_X509(bio.as_ptr(), self.x509) } != 1 {
+            return Err(Error::Ssl(None));
+        }
+        Ok(bio.contents())
+    }
+
+    /// Returns the DER encoding of the certificate.
+    pub fn to_der(&self) -> Result<Vec<u8>> {
+        let len = unsafe { ffi::i2d_X509(self.x509, ptr::null_mut()) };
+        if len < 0 {
+            return Err(Error::Ssl(None));
+        }
+        let mut der = vec![0; len as usize];
+        let mut outp = der.as_mut_ptr();
+        if unsafe { ffi::i2d_X509(self.x509, &mut outp) } != len {
+            return Err(Error::Ssl(None));
+        }
+        Ok(der)
+    }
+
+    /// Returns the subject name in RFC 2253 format, e.g. "CN=example.com,O=Example".
+    pub fn subject_name(&self) -> String {
+        name_to_string(unsafe { ffi::X509_get_subject_name(self.x509) })
+    }
+
+    /// Returns the issuer name in RFC 2253 format.
+    pub fn issuer_name(&self) -> String {
+        name_to_string(unsafe { ffi::X509_get_issuer_name(self.x509) })
+    }
+
+    /// Returns the serial number as big-endian bytes.
+    pub fn serial_number(&self) -> Vec<u8> {
+        unsafe { asn1_string_bytes(ffi::X509_get_serialNumber(self.x509)).to_vec() }
+    }
+
+    /// Returns the start of the validity period.
+    pub fn not_before(&self) -> Result<SystemTime> {
+        let (not_before, _) = try!(self.validity());
+        Ok(not_before)
+    }
+
+    /// Returns the end of the validity period.
+    pub fn not_after(&self) -> Result<SystemTime> {
+        let (_, not_after) = try!(self.validity());
+        Ok(not_after)
+    }
+
+    /// Reads the validity period from the DER encoding. BoringSSL only
+    /// provides macros over the X509 struct to access it.
+    fn validity(&self) -> Result<(SystemTime, SystemTime)> {
+        let der = try!(self.to_der());
+        match parse_validity(&der) {
+            Some(validity) => Ok(validity),
+            None => Err(Error::Ssl(None)),
+        }
+    }
+
+    /// Returns the algorithm of the subject public key.
+    pub fn public_key_algorithm(&self) -> Result<KeyAlgorithm> {
+        let pkey = unsafe { ffi::X509_get_pubkey(self.x509) };
+        if pkey.is_null() {
+            return Err(Error::Ssl(None));
+        }
+        let id = unsafe { ffi::EVP_PKEY_id(pkey) };
+        unsafe { ffi::EVP_PKEY_free(pkey) };
+        Ok(match id {
+            ffi::NID_rsaEncryption => KeyAlgorithm::Rsa,
+            ffi::NID_dsa => KeyAlgorithm::Dsa,
+            ffi::NID_X9_62_id_ecPublicKey => KeyAlgorithm::Ec,
+            ffi::NID_ED25519 => KeyAlgorithm::Ed25519,
+            n => KeyAlgorithm::Unknown(n),
+        })
+    }
+
+    /// Returns the size of the subject public key in bits.
+    pub fn public_key_bits(&self) -> Result<u32> {
+        let pkey = unsafe { ffi::X509_get_pubkey(self.x509) };
+        if pkey.is_null() {
+            return Err(Error::Ssl(None));
+        }
+        let bits = unsafe { ffi::EVP_PKEY_bits(pkey) };
+        unsafe { ffi::EVP_PKEY_free(pkey) };
+        Ok(bits as u32)
+    }
+
+    /// Returns the DNS, email, URI and IP address entries of the
+    /// subjectAltName extension. Other entry types are skipped.
+    pub fn subject_alt_names(&self) -> Vec<SubjectAltName> {
+        let mut sans = Vec::new();
+        unsafe {
+            let names = ffi::X509_get_ext_d2i(self.x509,
+                                              ffi::NID_subject_alt_name,
+                                              ptr::null_mut(),
+                                              ptr::null_mut());
+            if names.is_null() {
+                return sans;
+            }
+            for i in 0..ffi::sk_num(names) {
+                let name = &*(ffi::sk_value(names, i) as *const ffi::GENERAL_NAME);
+                let value = asn1_string_bytes(name.d);
+                let san = match name.type_ {
+                    ffi::GEN_DNS => SubjectAltName::Dns(String::from_utf8_lossy(value).into_owned()),
+                    ffi::GEN_EMAIL => {
+                        SubjectAltName::Email(String::from_utf8_lossy(value).into_owned())
+                    }
+                    ffi::GEN_URI => SubjectAltName::Uri(String::from_utf8_lossy(value).into_owned()),
+                    ffi::GEN_IPADD => {
+                        match ip_from_bytes(value) {
+                            Some(ip) => SubjectAltName::IpAddress(ip),
+                            None => continue,
+                        }
+                    }
+                    _ => continue,
+                };
+                sans.push(san);
+            }
+            ffi::GENERAL_NAMES_free(names);
+        }
+        sans
+    }
+
+    /// Returns the keyUsage extension, or None if it is absent.
+    pub fn key_usage(&self) -> Option<KeyUsage> {
+        unsafe {
+            let usage = ffi::X509_get_ext_d2i(self.x509,
+                                              ffi::NID_key_usage,
+                                              ptr::null_mut(),
+                                              ptr::null_mut());
+            if usage.is_null() {
+                return None;
+            }
+            // the KU_* values are the first byte of the bit string and the
+            // second shifted left by eight
+            let bits = asn1_string_bytes(usage)
+                .iter()
+                .take(2)
+                .enumerate()
+                .fold(0, |bits, (i, &b)| bits | (b as u32) << (8 * i));
+            ffi::ASN1_BIT_STRING_free(usage);
+            Some(KeyUsage(bits))
+        }
+    }
+
+    /// Returns the extendedKeyUsage extension, or None if it is absent.
+    /// Purposes without an ExtendedKeyUsage constant are skipped.
+    pub fn extended_key_usage(&self) -> Option<ExtendedKeyUsage> {
+        unsafe {
+            let purposes = ffi::X509_get_ext_d2i(self.x509,
+                                                 ffi::NID_ext_key_usage,
+                                                 ptr::null_mut(),
+                                                 ptr::null_mut());
+            if purposes.is_null() {
+                return None;
+            }
+            let mut bits = 0;
+            for i in 0..ffi::sk_num(purposes) {
+                bits |= match ffi::OBJ_obj2nid(ffi::sk_value(purposes, i)) {
+                    ffi::NID_server_auth => ffi::XKU_SSL_SERVER,
+                    ffi::NID_client_auth => ffi::XKU_SSL_CLIENT,
+                    ffi::NID_email_protect => ffi::XKU_SMIME,
+                    ffi::NID_code_sign => ffi::XKU_CODE_SIGN,
+                    ffi::NID_OCSP_sign => ffi::XKU_OCSP_SIGN,
+                    ffi::NID_time_stamp => ffi::XKU_TIMESTAMP,
+                    ffi::NID_anyExtendedKeyUsage => ffi::XKU_ANYEKU,
+                    _ => 0,
+                };
+            }
+            ffi::EXTENDED_KEY_USAGE_free(purposes);
+            Some(ExtendedKeyUsage(bits))
+        }
+    }
+
+    /// Returns the SHA-256 digest of the DER-encoded certificate.
+    pub fn sha256_fingerprint(&self) -> Result<[u8; 32]> {
+        let mut md = [0u8; ffi::EVP_MAX_MD_SIZE];
+        let mut len: c_uint = 0;
+        let ret_code =
+            unsafe { ffi::X509_digest(self.x509, ffi::EVP_sha256(), md.as_mut_ptr(), &mut len) };
+        if ret_code != 1 || len != 32 {
+            return Err(Error::Ssl(None));
+        }
+        let mut fingerprint = [0u8; 32];
+        fingerprint.copy_from_slice(&md[..32]);
+        Ok(fingerprint)
+    }
+}
+
+/// A certificate revocation list.
+/// The underlying X509_CRL is reference-counted, so cloning is cheap.
+pub struct X509Crl {
+    crl: *mut ffi::X509_CRL,
+}
+
+// The X509_CRL is immutable once parsed and reference-counted atomically.
+unsafe impl Send for X509Crl {}
+unsafe impl Sync for X509Crl {}
+
+impl Drop for X509Crl {
+    fn drop(&mut self) {
+        unsafe { ffi::X509_CRL_free(self.crl) }
+    }
+}
+
+impl Clone for X509Crl {
+    fn clone(&self) -> X509Crl {
+        unsafe { ffi::X509_CRL_up_ref(self.crl) };
+        X509Crl { crl: self.crl }
+    }
+}
+
+impl X509Crl {
+    pub(crate) fn as_ptr(&self) -> *mut ffi::X509_CRL {
+        self.crl
+    }
+
+    /// Parses the first PEM-encoded CRL in pem.
+    pub fn from_pem(pem: &[u8]) -> Result<X509Crl> {
+        let bio = try!(Bio::from_slice(pem));
+        let crl = unsafe {
+            ffi::PEM_read_bio_X509_CRL(bio.as_ptr(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut())
+        };
+        if crl.is_null() {
+            return Err(Error::Ssl(None));
+        }
+        Ok(X509Crl { crl: crl })
+    }
+
+    /// Parses a DER-encoded CRL.
+    pub fn from_der(der: &[u8]) -> Result<X509Crl> {
+        let mut inp = der.as_ptr();
+        let crl = unsafe { ffi::d2i_X509_CRL(ptr::null_mut(), &mut inp, der.len() as c_long) };
+        if crl.is_null() {
+            return Err(Error::Ssl(None));
+        }
+        Ok(X509Crl { crl: crl })
+    }
+}
+
+/// Selects which certificates of a chain are checked against the CRLs in the
+/// verification store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrlCheck {
+    /// Only the leaf certificate is checked.
+    Leaf,
+
+    /// Every certificate in the chain is checked.
+    Chain,
+}
+
+/// The reason certificate verification failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The certificate has been revoked by a CRL in the verification store.
+    CertificateRevoked,
+
+    /// CRL checking is enabled but no CRL was found for a certificate's issuer.
+    UnableToGetCrl,
+    UnableToGetCrlIssuer,
+    CrlSignatureFailure,
+    CrlNotYetValid,
+    CrlHasExpired,
+
+    UnableToGetIssuerCert,
+    UnableToGetIssuerCertLocally,
+    UnableToVerifyLeafSignature,
+    CertSignatureFailure,
+    CertNotYetValid,
+    CertHasExpired,
+    DepthZeroSelfSignedCert,
+    SelfSignedCertInChain,
+    CertChainTooLong,
+    InvalidCa,
+    InvalidPurpose,
+    CertUntrusted,
+
+    /// Any other X509_V_ERR_* value.
+    Other(i32),
+}
+
+impl VerifyError {
+    /// Converts an X509_V_* value, returning None for X509_V_OK.
+    pub(crate) fn from_raw(err: c_long) -> Option<VerifyError> {
+        Some(match err {
+            ffi::X509_V_OK => return None,
+            ffi::X509_V_ERR_CERT_REVOKED => VerifyError::CertificateRevoked,
+            ffi::X509_V_ERR_UNABLE_TO_GET_CRL => VerifyError::UnableToGetCrl,
+            ffi::X509_V_ERR_UNABLE_TO_GET_CRL_ISSUER => VerifyError::UnableToGetCrlIssuer,
+            ffi::X509_V_ERR_CRL_SIGNATURE_FAILURE => VerifyError::CrlSignatureFailure,
+            ffi::X509_V_ERR_CRL_NOT_YET_VALID => VerifyError::CrlNotYetValid,
+            ffi::X509_V_ERR_CRL_HAS_EXPIRED => VerifyError::CrlHasExpired,
+            ffi::X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT => VerifyError::UnableToGetIssuerCert,
+            ffi::X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT_LOCALLY => {
+                VerifyError::UnableToGetIssuerCertLocally
+            }
+            ffi::X509_V_ERR_UNABLE_TO_VERIFY_LEAF_SIGNATURE => {
+                VerifyError::UnableToVerifyLeafSignature
+            }
+            ffi::X509_V_ERR_CERT_SIGNATURE_FAILURE => VerifyError::CertSignatureFailure,
+            ffi::X509_V_ERR_CERT_NOT_YET_VALID => VerifyError::CertNotYetValid,
+            ffi::X509_V_ERR_CERT_HAS_EXPIRED => VerifyError::CertHasExpired,
+            ffi::X509_V_ERR_DEPTH_ZERO_SELF_SIGNED_CERT => VerifyError::DepthZeroSelfSignedCert,
+            ffi::X509_V_ERR_SELF_SIGNED_CERT_IN_CHAIN => VerifyError::SelfSignedCertInChain,
+            ffi::X509_V_ERR_CERT_CHAIN_TOO_LONG => VerifyError::CertChainTooLong,
+            ffi::X509_V_ERR_INVALID_CA => VerifyError::InvalidCa,
+            ffi::X509_V_ERR_INVALID_PURPOSE => VerifyError::InvalidPurpose,
+            ffi::X509_V_ERR_CERT_UNTRUSTED => VerifyError::CertUntrusted,
+            n => VerifyError::Other(n as i32),
+        })
+    }
+
+    fn to_raw(self) -> c_long {
+        match self {
+            VerifyError::CertificateRevoked => ffi::X509_V_ERR_CERT_REVOKED,
+            VerifyError::UnableToGetCrl => ffi::X509_V_ERR_UNABLE_TO_GET_CRL,
+            VerifyError::UnableToGetCrlIssuer => ffi::X509_V_ERR_UNABLE_TO_GET_CRL_ISSUER,
+            VerifyError::CrlSignatureFailure => ffi::X509_V_ERR_CRL_SIGNATURE_FAILURE,
+            VerifyError::CrlNotYetValid => ffi::X509_V_ERR_CRL_NOT_YET_VALID,
+            VerifyError::CrlHasExpired => ffi::X509_V_ERR_CRL_HAS_EXPIRED,
+            VerifyError::UnableToGetIssuerCert => ffi::X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT,
+            VerifyError::UnableToGetIssuerCertLocally => {
+                ffi::X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT_LOCALLY
+            }
+            VerifyError::UnableToVerifyLeafSignature => {
+                ffi::X509_V_ERR_UNABLE_TO_VERIFY_LEAF_SIGNATURE
+            }
+            VerifyError::CertSignatureFailure => ffi::X509_V_ERR_CERT_SIGNATURE_FAILURE,
+            VerifyError::CertNotYetValid => ffi::X509_V_ERR_CERT_NOT_YET_VALID,
+            VerifyError::CertHasExpired => ffi::X509_V_ERR_CERT_HAS_EXPIRED,
+            VerifyError::DepthZeroSelfSignedCert => ffi::X509_V_ERR_DEPTH_ZERO_SELF_SIGNED_CERT,
+            VerifyError::SelfSignedCertInChain => ffi::X509_V_ERR_SELF_SIGNED_CERT_IN_CHAIN,
+            VerifyError::CertChainTooLong => ffi::X509_V_ERR_CERT_CHAIN_TOO_LONG,
+            VerifyError::InvalidCa => ffi::X509_V_ERR_INVALID_CA,
+            VerifyError::InvalidPurpose => ffi::X509_V_ERR_INVALID_PURPOSE,
+            VerifyError::CertUntrusted => ffi::X509_V_ERR_CERT_UNTRUSTED,
+            VerifyError::Other(n) => n as c_long,
+        }
+    }
+
+    /// Returns true if verification failed because of a revocation check,
+    /// either because a certificate is revoked or because its CRL is unusable.
+    pub fn is_revocation(&self) -> bool {
+        matches!(*self,
+                 VerifyError::CertificateRevoked |
+                 VerifyError::UnableToGetCrl |
+                 VerifyError::UnableToGetCrlIssuer |
+                 VerifyError::CrlSignatureFailure |
+                 VerifyError::CrlNotYetValid |
+                 VerifyError::CrlHasExpired)
+    }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bs = unsafe {
+            let c_str = ffi::X509_verify_cert_error_string(self.to_raw());
+            CStr::from_ptr(c_str).to_bytes()
+        };
+        f.write_str(str::from_utf8(bs).unwrap())
+    }
+}
+
+/// Returns a copy of the certificates in a STACK_OF(X509), taking a new
+/// reference to each.
+pub(crate) unsafe fn stack_to_vec(sk: *const ffi::_STACK) -> Vec<X509> {
+    if sk.is_null() {
+        return Vec::new();
+    }
+    (0..ffi::sk_num(sk))
+        .map(|i| {
+            let x509 = ffi::sk_value(sk, i) as *mut ffi::X509;
+            ffi::X509_up_ref(x509);
+            X509::from_ptr(x509)
+        })
+        .collect()
+}
+
+fn name_to_string(name: *const ffi::X509_NAME) -> String {
+    let bio = match Bio::new_mem() {
+        Ok(bio) => bio,
+        Err(_) => return String::new(),
+    };
+    if unsafe { ffi::X509_NAME_print_ex(bio.as_ptr(), name, 0, ffi::XN_FLAG_RFC2253) } < 0 {
+        return String::new();
+    }
+    String::from_utf8_lossy(&bio.contents()).into_owned()
+}
+
+unsafe fn asn1_string_bytes<'a>(s: *const ffi::ASN1_STRING) -> &'a [u8] {
+    let len = ffi::ASN1_STRING_length(s);
+    if len <= 0 {
+        return &[];
+    }
+    slice::from_raw_parts(ffi::ASN1_STRING_get0_data(s), len as usize)
+}
+
+/// Iterates over the DER elements in a buffer, yielding the tag and the
+/// contents of each. Iteration stops at a malformed element.
+struct DerElements<'a>(&'a [u8]);
+
+impl<'a> Iterator for DerElements<'a> {
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<(u8, &'a [u8])> {
+        let der = self.0;
+        if der.len() < 2 {
+            return None;
+        }
+        // the length is in the second byte, or in up to four bytes after it
+        let (header, len) = match der[1] as usize {
+            len if len < 0x80 => (2, len),
+            n @ 0x81..=0x84 if der.len() >= 2 + (n & 0x7f) => {
+                let header = 2 + (n & 0x7f);
+                (header, der[2..header].iter().fold(0, |len, &b| len << 8 | b as usize))
+            }
+            _ => return None,
+        };
+        if der.len() - header < len {
+            return None;
+        }
+        self.0 = &der[header + len..];
+        Some((der[0], &der[header..header + len]))
+    }
+}
+
+/// Returns the notBefore and notAfter times of a DER-encoded certificate.
+fn parse_validity(der: &[u8]) -> Option<(SystemTime, SystemTime)> {
+    // Certificate ::= SEQUENCE { tbsCertificate TBSCertificate, ... }
+    let tbs = match DerElements(der).next().and_then(|(_, cert)| DerElements(cert).next()) {
+        Some((_, tbs)) => tbs,
+        None => return None,
+    };
+    // TBSCertificate ::= SEQUENCE { version [0] EXPLICIT Version DEFAULT v1,
+    //     serialNumber, signature, issuer, validity Validity, ... }
+    let validity = match DerElements(tbs).skip_while(|&(tag, _)| tag == 0xa0).nth(3) {
+        Some((0x30, validity)) => validity,
+        _ => return None,
+    };
+    // Validity ::= SEQUENCE { notBefore Time, notAfter Time }
+    let mut times = DerElements(validity).map(parse_time);
+    match (times.next(), times.next()) {
+        (Some(Some(not_before)), Some(Some(not_after))) => Some((not_before, not_after)),
+        _ => None,
+    }
+}
+
+/// The DER tags of the two forms of Time.
+const UTC_TIME: u8 = 23;
+const GENERALIZED_TIME: u8 = 24;
+
+/// Converts a UTCTime or GeneralizedTime in the YYMMDDHHMMSSZ and
+/// YYYYMMDDHHMMSSZ forms RFC 5280 requires of certificates.
+fn parse_time((tag, time): (u8, &[u8])) -> Option<SystemTime> {
+    let digits = match (tag, time.split_last()) {
+        (UTC_TIME, Some((&b'Z', digits))) if digits.len() == 12 => digits,
+        (GENERALIZED_TIME, Some((&b'Z', digits))) if digits.len() == 14 => digits,
+        _ => return None,
+    };
+    if !digits.iter().all(|d| d.is_ascii_digit()) {
+        return None;
+    }
+    let n: Vec<i64> = digits.chunks(2)
+        .map(|d| (d[0] - b'0') as i64 * 10 + (d[1] - b'0') as i64)
+        .collect();
+    // UTCTime years from 50 are in the 20th century
+    let (year, n) = match tag {
+        UTC_TIME if n[0] >= 50 => (1900 + n[0], &n[1..]),
+        UTC_TIME => (2000 + n[0], &n[1..]),
+        _ => (n[0] * 100 + n[1], &n[2..]),
+    };
+    let (month, day, hour, min, sec) = (n[0], n[1], n[2], n[3], n[4]);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || min > 59 ||
+       sec > 59 {
+        return None;
+    }
+    let secs = days_from_civil(year, month, day) * 86400 + hour * 3600 + min * 60 + sec;
+    if secs >= 0 {
+        Some(UNIX_EPOCH + Duration::from_secs(secs as u64))
+    } else {
+        Some(UNIX_EPOCH - Duration::from_secs((-secs) as u64))
+    }
+}
+
+/// Returns the number of days from 1970-01-01 to a date of the Gregorian
+/// calendar in a year from 1 on.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // count from March of year 0, so that the leap day is the last of a year
+    let y = if month <= 2 { year - 1 } else { year };
+    let (era, yoe) = (y / 400, y % 400);
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn ip_from_bytes(b: &[u8]) -> Option<IpAddr> {
+    match b.len() {
+        4 => Some(IpAddr::V4(Ipv4Addr::new(b[0], b[1], b[2], b[3]))),
+        16 => {
+            let mut segments = [0u16; 8];
+            for (i, s) in segments.iter_mut().enumerate() {
+                *s = (b[2 * i] as u16) << 8 | b[2 * i + 1] as u16;
+            }
+            Some(IpAddr::V6(Ipv6Addr::new(segments[0],
+                                          segments[1],
+                                          segments[2],
+                                          segments[3],
+                                          segments[4],
+                                          segments[5],
+                                          segments[6],
+                                          segments[7])))
+        }
+        _ => None,
+    }
+}
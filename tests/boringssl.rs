@@ -0,0 +1,22 @@
+//! BoringSSL's own test suites, built by build.rs against the same static
+//! libs as the crate, e.g. `cargo test --features boringssl-tests aead_test`.
+#![cfg(feature = "boringssl-tests")]
+
+use std::process::Command;
+
+/// Runs a test binary, which passes if it exits successfully and, unless it
+/// uses googletest, prints PASS last.
+#[allow(dead_code)] // if build.rs couldn't build any tests
+fn run(binary: &str, args: &[&str], gtest: bool) {
+    let output = Command::new(binary).args(args).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || !(gtest || stdout.trim_end().ends_with("PASS")) {
+        panic!("{} failed with {}:\n{}{}",
+               binary,
+               output.status,
+               stdout,
+               String::from_utf8_lossy(&output.stderr));
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/boringssl_tests.rs"));
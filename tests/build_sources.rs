@@ -0,0 +1,113 @@
+//! Tests the parsers build.rs uses to read the source lists written by
+//! generate_build_files.py against the small samples in tests/fixtures.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+include!("../build/sources.rs");
+
+fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+    let mut contents = String::new();
+    File::open(path).unwrap().read_to_string(&mut contents).unwrap();
+    contents
+}
+
+#[test]
+fn tokens() {
+    let tokens = tokenize("a = [\"b\\\"c\", # comment\n d](e)");
+    assert_eq!(tokens,
+               vec![Token::Word("a".to_string()),
+                    Token::Punct('='),
+                    Token::Punct('['),
+                    Token::Str("b\"c".to_string()),
+                    Token::Punct(','),
+                    Token::Word("d".to_string()),
+                    Token::Punct(']'),
+                    Token::Punct('('),
+                    Token::Word("e".to_string()),
+                    Token::Punct(')')]);
+}
+
+#[test]
+fn bazel() {
+    let lists = parse_lists(&tokenize(&fixture("BUILD.generated.bzl")));
+    assert_eq!(lists.len(), 8);
+    assert_eq!(lists[1],
+               ("ssl_sources".to_string(),
+                vec!["src/ssl/internal.h".to_string(),
+                     "src/ssl/ssl_lib.cc".to_string(),
+                     "src/ssl/tls_method.cc".to_string()]));
+
+    let sources = select_sources(lists.clone(), Some("linux_x86_64"));
+    assert_eq!(sources.c, ["err_data.c", "src/crypto/cpu-intel.c", "src/crypto/mem.c"]);
+    assert_eq!(sources.cc, ["src/ssl/ssl_lib.cc", "src/ssl/tls_method.cc"]);
+    assert_eq!(sources.asm,
+               ["linux-x86_64/crypto/chacha/chacha-x86_64.S",
+                "src/crypto/curve25519/asm/x25519-asm-x86_64.S"]);
+    assert!(sources.unknown.is_empty());
+
+    let sources = select_sources(lists.clone(), Some("win_x86_64"));
+    assert_eq!(sources.asm, ["win-x86_64/crypto/chacha/chacha-x86_64.asm"]);
+
+    // a platform without assembly, for which build.rs defines OPENSSL_NO_ASM
+    let sources = select_sources(lists.clone(), Some("linux_arm"));
+    assert!(sources.asm.is_empty());
+
+    let sources = select_sources(lists, None);
+    assert!(sources.asm.is_empty());
+    assert_eq!(sources.c.len(), 3);
+}
+
+#[test]
+fn json() {
+    let lists = parse_lists(&tokenize(&fixture("sources.json")));
+    assert_eq!(lists.len(), 6);
+
+    // the assembly of all platforms is listed together
+    let sources = select_sources(lists.clone(), Some("linux_x86_64"));
+    assert_eq!(sources.c,
+               ["src/crypto/fipsmodule/bcm.c", "src/crypto/mem.c", "src/gen/crypto/err_data.c"]);
+    assert_eq!(sources.cc, ["src/ssl/ssl_lib.cc"]);
+    assert_eq!(sources.asm,
+               ["src/gen/crypto/chacha-armv8-apple.S", "src/gen/crypto/chacha-x86_64-linux.S"]);
+    assert!(sources.unknown.is_empty());
+
+    let sources = select_sources(lists.clone(), Some("win_x86_64"));
+    assert_eq!(sources.asm, ["src/gen/crypto/chacha-x86_64-win.asm"]);
+
+    let sources = select_sources(lists, None);
+    assert!(sources.asm.is_empty());
+}
+
+#[test]
+fn cmake() {
+    let lists = parse_cmake(&tokenize(&fixture("sources.cmake")));
+    assert_eq!(lists.len(), 5);
+    assert_eq!(lists[1],
+               ("CRYPTO_SOURCES".to_string(),
+                vec!["src/crypto/mem.c".to_string(), "src/gen/crypto/err_data.c".to_string()]));
+
+    let sources = select_sources(lists.clone(), Some("linux_aarch64"));
+    assert_eq!(sources.c, ["src/crypto/mem.c", "src/gen/crypto/err_data.c"]);
+    assert_eq!(sources.cc, ["src/ssl/ssl_lib.cc", "src/ssl/tls_method.cc"]);
+    assert_eq!(sources.asm, ["src/gen/crypto/chacha-x86_64-linux.S"]);
+    assert_eq!(sources.unknown, ["DECREPIT_SOURCES"]);
+
+    let sources = select_sources(lists, Some("win_x86"));
+    assert_eq!(sources.asm, ["src/gen/crypto/chacha-x86_64-win.asm"]);
+}
+
+#[test]
+fn cc_tests() {
+    let tests = parse_cc_tests(&tokenize(&fixture("BUILD.generated_tests.bzl")));
+    assert_eq!(tests.len(), 2);
+    assert_eq!(tests[0].name, "aes_test");
+    assert_eq!(tests[0].srcs, ["src/crypto/aes/aes_test.cc"]);
+    assert_eq!(tests[0].args, ["$(location src/crypto/aes/aes_tests.txt)"]);
+    assert_eq!(tests[0].copts, ["-DBORINGSSL_SHARED_LIBRARY"]);
+    assert!(!tests[0].gtest);
+    assert_eq!(tests[1].name, "base64_test");
+    assert!(tests[1].args.is_empty());
+}
@@ -0,0 +1,72 @@
+#!/bin/sh
+# Generates the certificates and keys of the tests in this directory: the test
+# CA (ca.pem, ca.key), the localhost certificate it issued (server.pem,
+# server.der, server.key), a CRL of the CA revoking it (revoked-server.crl,
+# revoked-server.crl.der), the Channel ID key, a client certificate and a
+# self-signed, untrusted localhost certificate.
+# x509_parse in tests/test.rs checks the fingerprint and the validity of
+# server.pem, which have to be updated after regenerating it.
+
+cd "$(dirname "$0")" || exit 1
+
+DAYS=36500
+
+# test CA
+openssl req -x509 -newkey rsa:2048 -nodes -keyout ca.key -days $DAYS -sha256 \
+    -subj "/C=US/O=boringssl-rs/CN=Test CA" -addext "basicConstraints=critical,CA:TRUE" \
+    -addext "keyUsage=critical,keyCertSign,cRLSign" -out ca.pem || exit 1
+
+# server certificate for localhost, with the serial the CRL revokes
+openssl req -new -newkey rsa:2048 -nodes -keyout server.key \
+    -subj "/C=US/O=boringssl-rs/CN=localhost" -out server.csr || exit 1
+cat > server.ext <<EXT
+basicConstraints=CA:FALSE
+keyUsage=critical,digitalSignature,keyEncipherment
+extendedKeyUsage=serverAuth
+subjectAltName=DNS:localhost,IP:127.0.0.1
+subjectKeyIdentifier=hash
+authorityKeyIdentifier=keyid
+EXT
+openssl x509 -req -in server.csr -CA ca.pem -CAkey ca.key -set_serial 0x1001 -days $DAYS \
+    -sha256 -extfile server.ext -out server.pem || exit 1
+openssl x509 -in server.pem -outform DER -out server.der || exit 1
+rm -f server.csr server.ext
+
+# CRL revoking the server certificate
+mkdir -p crl || exit 1
+: > crl/index.txt
+echo 1000 > crl/crlnumber
+cat > crl/ca.cnf <<CNF
+[ca]
+default_ca = test_ca
+
+[test_ca]
+database = crl/index.txt
+crlnumber = crl/crlnumber
+certificate = ca.pem
+private_key = ca.key
+default_md = sha256
+default_crl_days = $DAYS
+CNF
+openssl ca -config crl/ca.cnf -revoke server.pem || exit 1
+openssl ca -config crl/ca.cnf -gencrl -out revoked-server.crl || exit 1
+openssl crl -in revoked-server.crl -outform DER -out revoked-server.crl.der || exit 1
+rm -rf crl
+
+# P-256 key of the client's Channel ID
+openssl genpkey -algorithm EC -pkeyopt ec_paramgen_curve:P-256 -out channel_id.key || exit 1
+
+# client certificate for client authentication
+openssl req -new -newkey rsa:2048 -nodes -keyout client.key -subj "/C=US/O=boringssl-rs/CN=client" \
+    -out client.csr || exit 1
+printf "extendedKeyUsage=clientAuth\n" > client.ext
+openssl x509 -req -in client.csr -CA ca.pem -CAkey ca.key -CAcreateserial -days $DAYS -sha256 \
+    -extfile client.ext -out client.pem || exit 1
+rm -f client.csr client.ext ca.srl
+
+# self-signed certificate for localhost, which the test CA didn't issue
+openssl req -x509 -newkey rsa:2048 -nodes -keyout untrusted.key -days $DAYS -sha256 \
+    -subj "/C=US/O=boringssl-rs/CN=localhost" -addext "subjectAltName=DNS:localhost,IP:127.0.0.1" \
+    -out untrusted.pem || exit 1
+
+echo "done!"
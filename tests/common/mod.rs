@@ -0,0 +1,158 @@
+//! Helpers shared by the integration tests to create contexts and to connect
+//! a client and a server over a non-blocking socket pair.
+#![allow(dead_code)]
+
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+
+use boringssl::{AlertDescription, Client, Context, ContextBuilder, Error, PrivateKey, Server,
+                X509};
+
+pub fn cert(pem: &[u8]) -> X509 {
+    X509::from_pem(pem).unwrap()
+}
+
+pub fn key(pem: &[u8]) -> PrivateKey {
+    PrivateKey::from_pem(pem).unwrap()
+}
+
+/// Returns a server context builder with the localhost certificate issued by
+/// the test CA.
+pub fn server_context() -> ContextBuilder {
+    let mut ctx = ContextBuilder::new().unwrap();
+    ctx.set_certificate(&cert(include_bytes!("../certs/server.pem"))).unwrap();
+    ctx.set_private_key(&key(include_bytes!("../certs/server.key"))).unwrap();
+    ctx
+}
+
+/// Creates a client and a server connected over a non-blocking socket pair.
+/// The sockets must outlive the connections.
+pub fn connect(client_ctx: &Context,
+               server_ctx: &Context)
+               -> (Client, Server, (UnixStream, UnixStream)) {
+    let (client_sock, server_sock) = UnixStream::pair().unwrap();
+    client_sock.set_nonblocking(true).unwrap();
+    server_sock.set_nonblocking(true).unwrap();
+    let client = Client::new_socket(client_ctx, client_sock.as_raw_fd()).unwrap();
+    let server = Server::new_socket(server_ctx, server_sock.as_raw_fd()).unwrap();
+    (client, server, (client_sock, server_sock))
+}
+
+/// Runs both ends of the handshake until each completed or failed. Errors
+/// other than WantRead and WantWrite are passed to retry, and the end is
+/// continued if it returns true. An end still waiting for its peer reports
+/// WantRead.
+pub fn try_handshake<F>(client: &mut Client,
+                        server: &mut Server,
+                        mut retry: F)
+                        -> (Result<(), Error>, Result<(), Error>)
+    where F: FnMut(&Error) -> bool
+{
+    let (mut client_result, mut server_result) = (Err(Error::WantRead), Err(Error::WantRead));
+    for _ in 0..100 {
+        if would_block(&client_result) {
+            client_result = retried(client.handshake(), &mut retry);
+        }
+        if would_block(&server_result) {
+            server_result = retried(server.handshake(), &mut retry);
+        }
+        if !would_block(&client_result) && !would_block(&server_result) {
+            break;
+        }
+    }
+    (client_result, server_result)
+}
+
+/// Reports an error as WantRead if retry returns true for it.
+fn retried<F>(result: Result<(), Error>, retry: &mut F) -> Result<(), Error>
+    where F: FnMut(&Error) -> bool
+{
+    match result {
+        Err(ref err) if !would_block(&result) && retry(err) => Err(Error::WantRead),
+        result => result,
+    }
+}
+
+/// Runs the handshake on both ends until it completes, as try_handshake.
+/// Panics if either end fails.
+pub fn handshake<F>(client: &mut Client, server: &mut Server, retry: F)
+    where F: FnMut(&Error) -> bool
+{
+    match try_handshake(client, server, retry) {
+        (Ok(()), Ok(())) => {}
+        results => panic!("handshake failed: {:?}", results),
+    }
+}
+
+/// A client and a server connected over a non-blocking socket pair.
+pub struct Pair {
+    pub client: Client,
+    pub server: Server,
+    _socks: (UnixStream, UnixStream),
+}
+
+impl Pair {
+    pub fn new(client_ctx: &Context, server_ctx: &Context) -> Pair {
+        let (client, server, _socks) = connect(client_ctx, server_ctx);
+        Pair { client, server, _socks }
+    }
+
+    /// Runs both ends of the handshake until each completed or failed.
+    pub fn handshake(&mut self) -> (Result<(), Error>, Result<(), Error>) {
+        try_handshake(&mut self.client, &mut self.server, |_| false)
+    }
+
+    pub fn connect(client_ctx: &Context, server_ctx: &Context) -> Pair {
+        let mut pair = Pair::new(client_ctx, server_ctx);
+        match pair.handshake() {
+            (Ok(()), Ok(())) => pair,
+            results => panic!("handshake failed: {:?}", results),
+        }
+    }
+
+    /// Sends data from the client to the server and back, reading and
+    /// writing in turns as the sockets would block.
+    pub fn echo(&mut self, data: &[u8]) {
+        let mut received = Vec::new();
+        let mut echoed = Vec::new();
+        let (mut sent, mut sent_back) = (0, 0);
+        let mut buf = [0; 16384];
+        for _ in 0..100000 {
+            if sent < data.len() {
+                sent += ok_or_blocked(self.client.write(&data[sent..]));
+            }
+            let n = ok_or_blocked(self.server.read(&mut buf));
+            received.extend_from_slice(&buf[..n]);
+            if sent_back < received.len() {
+                sent_back += ok_or_blocked(self.server.write(&received[sent_back..]));
+            }
+            let n = ok_or_blocked(self.client.read(&mut buf));
+            echoed.extend_from_slice(&buf[..n]);
+            if echoed.len() == data.len() {
+                assert!(echoed == data);
+                return;
+            }
+        }
+        panic!("echoed {} of {} bytes", echoed.len(), data.len());
+    }
+}
+
+pub fn would_block<T>(result: &Result<T, Error>) -> bool {
+    matches!(*result, Err(Error::WantRead) | Err(Error::WantWrite))
+}
+
+/// Returns the bytes read or written, or zero if the socket would block.
+pub fn ok_or_blocked(result: Result<usize, Error>) -> usize {
+    match result {
+        Ok(n) => n,
+        Err(Error::WantRead) | Err(Error::WantWrite) => 0,
+        Err(err) => panic!("{:?}", err),
+    }
+}
+
+pub fn alert(result: &Result<(), Error>) -> Option<AlertDescription> {
+    match *result {
+        Err(Error::Ssl(Some(alert))) => Some(alert.description()),
+        _ => None,
+    }
+}
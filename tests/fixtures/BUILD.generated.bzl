@@ -0,0 +1,38 @@
+# This file is created by generate_build_files.py. Do not edit manually.
+
+ssl_headers = [
+    "src/include/openssl/ssl.h",
+]
+
+ssl_sources = [
+    "src/ssl/internal.h",
+    "src/ssl/ssl_lib.cc",
+    "src/ssl/tls_method.cc",
+]
+
+crypto_internal_headers = [
+    "src/crypto/internal.h",
+]
+
+crypto_sources = [
+    "err_data.c",
+    "src/crypto/mem.c",
+    "src/crypto/cpu-intel.c",
+]
+
+tool_sources = [
+    "src/tool/tool.cc",
+]
+
+crypto_sources_linux_x86_64 = [
+    "linux-x86_64/crypto/chacha/chacha-x86_64.S",
+    "src/crypto/curve25519/asm/x25519-asm-x86_64.S",
+]
+
+crypto_sources_mac_x86_64 = [
+    "mac-x86_64/crypto/chacha/chacha-x86_64.S",
+]
+
+crypto_sources_win_x86_64 = [
+    "win-x86_64/crypto/chacha/chacha-x86_64.asm",
+]
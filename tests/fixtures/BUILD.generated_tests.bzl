@@ -0,0 +1,29 @@
+# This file is created by generate_build_files.py. Do not edit manually.
+
+test_support_sources = [
+    "src/crypto/test/file_test.cc",
+    "src/crypto/test/test_util.cc",
+]
+
+def create_tests(copts, crypto, ssl):
+  native.cc_test(
+      name = "aes_test",
+      size = "small",
+      srcs = ["src/crypto/aes/aes_test.cc"] + test_support_sources,
+      args = [
+          "$(location src/crypto/aes/aes_tests.txt)",
+      ],
+      copts = copts + ["-DBORINGSSL_SHARED_LIBRARY"],
+      data = [
+          "src/crypto/aes/aes_tests.txt",
+      ],
+      deps = [crypto],
+  )
+
+  native.cc_test(
+      name = "base64_test",
+      size = "small",
+      srcs = ["src/crypto/base64/base64_test.cc"] + test_support_sources,
+      copts = copts + ["-DBORINGSSL_SHARED_LIBRARY"],
+      deps = [crypto],
+  )
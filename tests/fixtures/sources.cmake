@@ -0,0 +1,35 @@
+# Copyright (c) 2024 The BoringSSL Authors
+#
+# Generated by "go run ./util/pregenerate". Do not edit manually.
+
+set(
+  SSL_SOURCES
+
+  src/ssl/ssl_lib.cc
+  src/ssl/tls_method.cc
+)
+
+set(
+  CRYPTO_SOURCES
+
+  src/crypto/mem.c
+  "src/gen/crypto/err_data.c"
+)
+
+set(
+  CRYPTO_SOURCES_ASM
+
+  src/gen/crypto/chacha-x86_64-linux.S
+)
+
+set(
+  CRYPTO_SOURCES_NASM
+
+  src/gen/crypto/chacha-x86_64-win.asm
+)
+
+set(
+  DECREPIT_SOURCES
+
+  src/decrepit/bio/base64_bio.c
+)
@@ -0,0 +1,324 @@
+//! End-to-end handshakes between a client and a server over local transports,
+//! for each TLS version. The certificates are generated by certs/generate.sh;
+//! no network access is needed.
+
+extern crate boringssl;
+
+mod common;
+
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::io::AsRawFd;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use boringssl::{AlertDescription, Client, ContextBuilder, CrlCheck, Error, Server,
+                ServerNameAction, VerifyError, VerifyMode, X509Crl};
+
+use common::{Pair, alert, cert, key, server_context};
+
+const VERSIONS: &[u16] = &[boringssl::TLS1_2_VERSION, boringssl::TLS1_3_VERSION];
+
+/// Returns a server context builder for version, with the localhost
+/// certificate issued by the test CA.
+fn server_builder(version: u16) -> ContextBuilder {
+    let mut ctx = server_context();
+    ctx.set_min_version(version);
+    ctx.set_max_version(version);
+    ctx
+}
+
+/// Returns a client context builder for version, which trusts the test CA.
+fn client_builder(version: u16) -> ContextBuilder {
+    let mut ctx = ContextBuilder::new().unwrap();
+    ctx.set_verify(VerifyMode::Peer);
+    ctx.add_trusted_cert(&cert(include_bytes!("certs/ca.pem"))).unwrap();
+    ctx.set_min_version(version);
+    ctx.set_max_version(version);
+    ctx
+}
+
+#[test]
+fn handshake_and_echo() {
+    for &version in VERSIONS {
+        let server_ctx = server_builder(version).build();
+        let client_ctx = client_builder(version).build();
+        let mut pair = Pair::connect(&client_ctx, &server_ctx);
+
+        assert_eq!(pair.client.info().version, version);
+        assert_eq!(pair.server.info().version, version);
+        assert!(pair.client.verify_result().is_ok());
+        let server_cert = cert(include_bytes!("certs/server.pem"));
+        assert_eq!(pair.client.info().peer_certificate_sha256,
+                   Some(server_cert.sha256_fingerprint().unwrap()));
+        assert!(pair.server.peer_certificate().is_none());
+        pair.echo(b"hello");
+    }
+}
+
+/// Moves more data than fits in the socket buffers, so that both ends have to
+/// retry reads and writes.
+#[test]
+fn non_blocking_bulk_transfer() {
+    for &version in VERSIONS {
+        let server_ctx = server_builder(version).build();
+        let client_ctx = client_builder(version).build();
+        let mut pair = Pair::connect(&client_ctx, &server_ctx);
+        let data: Vec<u8> = (0..1 << 20).map(|i| i as u8).collect();
+        pair.echo(&data);
+    }
+}
+
+/// A blocking server on its own thread and a blocking client over TCP on the
+/// loopback interface.
+#[test]
+fn tcp_loopback() {
+    for &version in VERSIONS {
+        let server_ctx = server_builder(version).build();
+        let client_ctx = client_builder(version).build();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (sock, _) = listener.accept().unwrap();
+            let mut server = Server::new_socket(&server_ctx, sock.as_raw_fd()).unwrap();
+            server.handshake().unwrap();
+            let mut buf = [0; 5];
+            assert_eq!(server.read(&mut buf).unwrap(), 5);
+            assert_eq!(server.write(&buf).unwrap(), 5);
+            drop(server);
+            drop(sock);
+        });
+
+        let sock = TcpStream::connect(addr).unwrap();
+        let mut client = Client::new_socket(&client_ctx, sock.as_raw_fd()).unwrap();
+        client.set_hostname("localhost").unwrap();
+        client.handshake().unwrap();
+        assert_eq!(client.info().version, version);
+        assert_eq!(client.write(b"hello").unwrap(), 5);
+        let mut buf = [0; 5];
+        assert_eq!(client.read(&mut buf).unwrap(), 5);
+        assert_eq!(&buf, b"hello");
+        server.join().unwrap();
+    }
+}
+
+#[test]
+fn session_resumption() {
+    for &version in VERSIONS {
+        let server_ctx = server_builder(version).build();
+        let sessions = Arc::new(Mutex::new(Vec::new()));
+        let mut client_ctx = client_builder(version);
+        let client_sessions = sessions.clone();
+        client_ctx.set_new_session_callback(move |session| {
+            client_sessions.lock().unwrap().push(session)
+        });
+        let client_ctx = client_ctx.build();
+
+        // in TLS 1.3 the session arrives after the handshake
+        let mut pair = Pair::connect(&client_ctx, &server_ctx);
+        assert!(!pair.client.info().session_resumed);
+        pair.echo(b"hello");
+        let session = sessions.lock().unwrap().pop().expect("no session");
+
+        let mut pair = Pair::new(&client_ctx, &server_ctx);
+        pair.client.set_session(&session).unwrap();
+        match pair.handshake() {
+            (Ok(()), Ok(())) => {}
+            results => panic!("resumption failed: {:?}", results),
+        }
+        assert!(pair.client.info().session_resumed, "version {:x}", version);
+        assert!(pair.server.info().session_resumed, "version {:x}", version);
+        pair.echo(b"hello again");
+    }
+}
+
+#[test]
+fn alpn() {
+    for &version in VERSIONS {
+        let mut server_ctx = server_builder(version);
+        server_ctx.set_alpn_protocols(&[b"h2", b"http/1.1"]).unwrap();
+        let server_ctx = server_ctx.build();
+        let client_ctx = client_builder(version).build();
+
+        // the server's preference wins
+        let mut pair = Pair::new(&client_ctx, &server_ctx);
+        pair.client.set_alpn_protos(&[b"http/1.1", b"h2"]).unwrap();
+        assert!(pair.handshake().0.is_ok());
+        assert_eq!(pair.client.info().alpn_protocol, Some(b"h2".to_vec()));
+        assert_eq!(pair.server.info().alpn_protocol, Some(b"h2".to_vec()));
+
+        let mut pair = Pair::new(&client_ctx, &server_ctx);
+        pair.client.set_alpn_protos(&[b"spdy/3"]).unwrap();
+        assert!(pair.handshake().0.is_ok());
+        assert_eq!(pair.client.info().alpn_protocol, None);
+
+        let pair = Pair::connect(&client_ctx, &server_ctx);
+        assert_eq!(pair.server.info().alpn_protocol, None);
+    }
+}
+
+#[test]
+fn sni() {
+    for &version in VERSIONS {
+        let server_ctx = server_builder(version).build();
+        let client_ctx = client_builder(version).build();
+
+        let mut pair = Pair::new(&client_ctx, &server_ctx);
+        pair.client.set_hostname("localhost").unwrap();
+        assert!(pair.handshake().1.is_ok());
+        assert_eq!(pair.server.server_name(), Some("localhost"));
+        assert_eq!(pair.server.info().server_name, Some("localhost".to_string()));
+
+        let pair = Pair::connect(&client_ctx, &server_ctx);
+        assert_eq!(pair.server.server_name(), None);
+    }
+}
+
+#[test]
+fn client_auth() {
+    for &version in VERSIONS {
+        let mut server_ctx = server_builder(version);
+        server_ctx.set_verify(VerifyMode::Peer);
+        server_ctx.add_trusted_cert(&cert(include_bytes!("certs/ca.pem"))).unwrap();
+        let server_ctx = server_ctx.build();
+        let mut client_ctx = client_builder(version);
+        client_ctx.set_certificate(&cert(include_bytes!("certs/client.pem"))).unwrap();
+        client_ctx.set_private_key(&key(include_bytes!("certs/client.key"))).unwrap();
+        let client_ctx = client_ctx.build();
+
+        let pair = Pair::connect(&client_ctx, &server_ctx);
+        assert!(pair.server.verify_result().is_ok());
+        let client_cert = cert(include_bytes!("certs/client.pem"));
+        assert_eq!(pair.server.info().peer_certificate_sha256,
+                   Some(client_cert.sha256_fingerprint().unwrap()));
+        assert_eq!(pair.server.peer_certificate().unwrap().subject_name(),
+                   client_cert.subject_name());
+
+        // without a certificate, the client stays anonymous
+        let client_ctx = client_builder(version).build();
+        let pair = Pair::connect(&client_ctx, &server_ctx);
+        assert!(pair.server.peer_certificate().is_none());
+    }
+}
+
+#[test]
+fn untrusted_client_certificate() {
+    for &version in VERSIONS {
+        let mut server_ctx = server_builder(version);
+        server_ctx.set_verify(VerifyMode::Peer);
+        server_ctx.add_trusted_cert(&cert(include_bytes!("certs/ca.pem"))).unwrap();
+        let server_ctx = server_ctx.build();
+        let mut client_ctx = client_builder(version);
+        client_ctx.set_certificate(&cert(include_bytes!("certs/untrusted.pem"))).unwrap();
+        client_ctx.set_private_key(&key(include_bytes!("certs/untrusted.key"))).unwrap();
+        let client_ctx = client_ctx.build();
+
+        let mut pair = Pair::new(&client_ctx, &server_ctx);
+        let (_, server) = pair.handshake();
+        assert!(matches!(server, Err(Error::Verify(_))), "{:?}", server);
+        assert!(pair.server.verify_result().is_err());
+    }
+}
+
+#[test]
+fn untrusted_server_certificate() {
+    for &version in VERSIONS {
+        let mut server_ctx = ContextBuilder::new().unwrap();
+        server_ctx.set_certificate(&cert(include_bytes!("certs/untrusted.pem"))).unwrap();
+        server_ctx.set_private_key(&key(include_bytes!("certs/untrusted.key"))).unwrap();
+        let server_ctx = server_ctx.build();
+        let client_ctx = client_builder(version).build();
+
+        let mut pair = Pair::new(&client_ctx, &server_ctx);
+        let (client, server) = pair.handshake();
+        assert!(matches!(client, Err(Error::Verify(_))), "{:?}", client);
+        assert!(server.is_err());
+        assert!(pair.client.verify_result().is_err());
+
+        // without verification, the same handshake succeeds
+        let client_ctx = ContextBuilder::new().unwrap().build();
+        Pair::connect(&client_ctx, &server_ctx);
+    }
+}
+
+#[test]
+fn revoked_server_certificate() {
+    let crl = X509Crl::from_pem(include_bytes!("certs/revoked-server.crl")).unwrap();
+    for &version in VERSIONS {
+        let server_ctx = server_builder(version).build();
+        let mut client_ctx = client_builder(version);
+        client_ctx.add_crl(&crl).unwrap();
+        client_ctx.set_crl_check(CrlCheck::Leaf);
+        let client_ctx = client_ctx.build();
+
+        let mut pair = Pair::new(&client_ctx, &server_ctx);
+        let (client, server) = pair.handshake();
+        assert!(matches!(client, Err(Error::Verify(VerifyError::CertificateRevoked))),
+                "{:?}",
+                client);
+        assert!(server.is_err());
+        assert_eq!(pair.client.verify_result(), Err(VerifyError::CertificateRevoked));
+
+        // without the CRL check, the CRL is ignored
+        let mut client_ctx = client_builder(version);
+        client_ctx.add_crl(&crl).unwrap();
+        let client_ctx = client_ctx.build();
+        Pair::connect(&client_ctx, &server_ctx);
+    }
+}
+
+/// The server's default certificate isn't issued by the test CA; the callback
+/// switches to the localhost context for localhost and rejects other names.
+#[test]
+fn server_name_callback() {
+    for &version in VERSIONS {
+        let localhost = server_builder(version).build();
+        let mut server_ctx = ContextBuilder::new().unwrap();
+        server_ctx.set_certificate(&cert(include_bytes!("certs/untrusted.pem"))).unwrap();
+        server_ctx.set_private_key(&key(include_bytes!("certs/untrusted.key"))).unwrap();
+        server_ctx.set_min_version(version);
+        server_ctx.set_max_version(version);
+        server_ctx.set_server_name_callback(move |req| {
+            match req.server_name() {
+                Some("localhost") => {
+                    req.set_context(&localhost).unwrap();
+                    ServerNameAction::Accept
+                }
+                _ => ServerNameAction::Reject,
+            }
+        });
+        let server_ctx = server_ctx.build();
+        let client_ctx = client_builder(version).build();
+
+        let mut pair = Pair::new(&client_ctx, &server_ctx);
+        pair.client.set_hostname("localhost").unwrap();
+        match pair.handshake() {
+            (Ok(()), Ok(())) => {}
+            results => panic!("handshake failed: {:?}", results),
+        }
+        assert_eq!(pair.server.server_name(), Some("localhost"));
+        let server_cert = cert(include_bytes!("certs/server.pem"));
+        assert_eq!(pair.client.info().peer_certificate_sha256,
+                   Some(server_cert.sha256_fingerprint().unwrap()));
+        pair.echo(b"hello");
+
+        let mut pair = Pair::new(&client_ctx, &server_ctx);
+        pair.client.set_hostname("example.com").unwrap();
+        let (client, server) = pair.handshake();
+        assert!(server.is_err());
+        assert_eq!(alert(&client), Some(AlertDescription::UnrecognizedName));
+    }
+}
+
+#[test]
+fn version_mismatch() {
+    let server_ctx = server_builder(boringssl::TLS1_3_VERSION).build();
+    let client_ctx = client_builder(boringssl::TLS1_2_VERSION).build();
+
+    let mut pair = Pair::new(&client_ctx, &server_ctx);
+    let (client, server) = pair.handshake();
+    // neither end verified a certificate, so the alert is reported
+    assert!(matches!(server, Err(Error::Ssl(Some(_)))), "{:?}", server);
+    assert_eq!(alert(&server), Some(AlertDescription::ProtocolVersion));
+    assert_eq!(alert(&client), Some(AlertDescription::ProtocolVersion));
+}
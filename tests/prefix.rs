@@ -0,0 +1,44 @@
+//! Links the system OpenSSL next to BoringSSL, which only works when
+//! BoringSSL's symbols are prefixed, e.g. `cargo test --features prefix-symbols`.
+//! build.rs links OpenSSL's libs by path and sets link_openssl if it finds
+//! them.
+#![cfg(all(feature = "prefix-symbols", link_openssl))]
+
+extern crate boringssl;
+
+mod common;
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_void};
+
+use common::{connect, handshake, server_context};
+
+extern "C" {
+    fn OpenSSL_version(t: c_int) -> *const c_char;
+    fn TLS_method() -> *const c_void;
+    fn SSL_CTX_new(method: *const c_void) -> *mut c_void;
+    fn SSL_CTX_free(ctx: *mut c_void);
+}
+
+#[test]
+fn coexists_with_openssl() {
+    // the unprefixed names resolve to OpenSSL
+    let version = unsafe { CStr::from_ptr(OpenSSL_version(0)) };
+    assert!(version.to_str().unwrap().starts_with("OpenSSL"));
+    unsafe {
+        let ctx = SSL_CTX_new(TLS_method());
+        assert!(!ctx.is_null());
+        SSL_CTX_free(ctx);
+    }
+
+    // and the crate still uses BoringSSL
+    let server_ctx = server_context().build();
+    let client_ctx = boringssl::Context::new().unwrap();
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    handshake(&mut client, &mut server, |_| false);
+
+    assert_eq!(client.write(b"ping").unwrap(), 4);
+    let mut buf = [0; 4];
+    assert_eq!(server.read(&mut buf).unwrap(), 4);
+    assert_eq!(&buf, b"ping");
+}
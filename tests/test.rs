@@ -1,9 +1,21 @@
 extern crate boringssl;
 
+mod common;
+
+use std::io::{self, Read, Write};
+use std::net::UdpSocket;
+use std::sync::{Arc, Mutex};
+#[cfg(boringssl_private_key_method)]
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+
+use common::{connect, handshake, server_context};
 
 #[test]
 fn it_compiles() {
-    let mut ssl_ctx = match boringssl::Context::new() {
+    let mut ssl_ctx = match boringssl::ContextBuilder::new() {
         Ok(ctx) => ctx,
         Err(_) => {
             panic!("SSL_CTX failed");
@@ -16,3 +28,713 @@ fn it_compiles() {
     ssl_ctx.enable_ocsp_stapling();
     ssl_ctx.enable_tls_channel_id();
 }
+
+#[test]
+fn x509_parse() {
+    let pem = include_bytes!("certs/server.pem");
+    let der = include_bytes!("certs/server.der");
+
+    let cert = boringssl::X509::from_pem(pem).unwrap();
+    assert_eq!(cert.to_der().unwrap(), &der[..]);
+    assert_eq!(boringssl::X509::from_der(der).unwrap().to_pem().unwrap(), &pem[..]);
+
+    assert_eq!(cert.subject_name(), "CN=localhost,O=boringssl-rs,C=US");
+    assert_eq!(cert.issuer_name(), "CN=Test CA,O=boringssl-rs,C=US");
+    assert_eq!(cert.serial_number(), vec![0x10, 0x01]);
+    // notBefore is a UTCTime, notAfter in 2126 a GeneralizedTime
+    assert_eq!(cert.not_before().unwrap(), UNIX_EPOCH + Duration::from_secs(1792377584));
+    assert_eq!(cert.not_after().unwrap(), UNIX_EPOCH + Duration::from_secs(4945977584));
+    assert_eq!(cert.public_key_algorithm().unwrap(), boringssl::KeyAlgorithm::Rsa);
+    assert_eq!(cert.public_key_bits().unwrap(), 2048);
+    assert_eq!(cert.subject_alt_names(),
+               vec![boringssl::SubjectAltName::Dns("localhost".to_string()),
+                    boringssl::SubjectAltName::IpAddress("127.0.0.1".parse().unwrap())]);
+
+    let ku = cert.key_usage().unwrap();
+    assert!(ku.contains(boringssl::KeyUsage::DIGITAL_SIGNATURE));
+    assert!(ku.contains(boringssl::KeyUsage::KEY_ENCIPHERMENT));
+    assert!(!ku.contains(boringssl::KeyUsage::KEY_CERT_SIGN));
+    assert_eq!(cert.extended_key_usage(),
+               Some(boringssl::ExtendedKeyUsage::SERVER_AUTH));
+
+    let ca = boringssl::X509::from_pem(include_bytes!("certs/ca.pem")).unwrap();
+    assert_eq!(ca.key_usage().unwrap().bits(),
+               boringssl::KeyUsage::KEY_CERT_SIGN.bits() | boringssl::KeyUsage::CRL_SIGN.bits());
+    assert_eq!(ca.extended_key_usage(), None);
+    let client = boringssl::X509::from_pem(include_bytes!("certs/client.pem")).unwrap();
+    assert_eq!(client.key_usage(), None);
+    assert_eq!(client.extended_key_usage(),
+               Some(boringssl::ExtendedKeyUsage::CLIENT_AUTH));
+
+    assert_eq!(&cert.sha256_fingerprint().unwrap()[..4], &[0x11, 0x4c, 0xdb, 0x29]);
+}
+
+#[test]
+fn crl_store() {
+    let ca = boringssl::X509::from_pem(include_bytes!("certs/ca.pem")).unwrap();
+    let crl = boringssl::X509Crl::from_pem(include_bytes!("certs/revoked-server.crl")).unwrap();
+    boringssl::X509Crl::from_der(include_bytes!("certs/revoked-server.crl.der")).unwrap();
+    assert!(boringssl::X509Crl::from_der(b"not a crl").is_err());
+
+    let mut ssl_ctx = boringssl::ContextBuilder::new().unwrap();
+    ssl_ctx.set_verify(boringssl::VerifyMode::Peer);
+    ssl_ctx.add_trusted_cert(&ca).unwrap();
+    ssl_ctx.add_crl(&crl).unwrap();
+    ssl_ctx.set_crl_check(boringssl::CrlCheck::Chain);
+    ssl_ctx.set_crl_check(boringssl::CrlCheck::Leaf);
+    let client_ctx = ssl_ctx.build();
+
+    // the CRL revokes the server's certificate
+    let server_ctx = server_context().build();
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    let mut result = Err(boringssl::Error::WantRead);
+    for _ in 0..100 {
+        result = client.handshake();
+        let _ = server.handshake();
+        if !matches!(result, Err(boringssl::Error::WantRead)) {
+            break;
+        }
+    }
+    assert!(matches!(result,
+                     Err(boringssl::Error::Verify(boringssl::VerifyError::CertificateRevoked))),
+            "{:?}",
+            result);
+    assert_eq!(client.verify_result(), Err(boringssl::VerifyError::CertificateRevoked));
+    assert!(boringssl::VerifyError::CertificateRevoked.is_revocation());
+}
+
+#[test]
+fn server_name_callback() {
+    let cert = boringssl::X509::from_pem(include_bytes!("certs/server.pem")).unwrap();
+    let key = boringssl::PrivateKey::from_pem(include_bytes!("certs/server.key")).unwrap();
+
+    let mut localhost = boringssl::ContextBuilder::new().unwrap();
+    localhost.set_certificate(&cert).unwrap();
+    localhost.set_private_key(&key).unwrap();
+    let localhost = localhost.build();
+
+    // the default certificate isn't issued by the CA the client trusts
+    let untrusted = boringssl::X509::from_pem(include_bytes!("certs/untrusted.pem")).unwrap();
+    let untrusted_key =
+        boringssl::PrivateKey::from_pem(include_bytes!("certs/untrusted.key")).unwrap();
+    let mut ssl_ctx = boringssl::ContextBuilder::new().unwrap();
+    ssl_ctx.set_certificate(&untrusted).unwrap();
+    ssl_ctx.set_private_key(&untrusted_key).unwrap();
+    ssl_ctx.set_server_name_callback(move |req| {
+        match req.server_name() {
+            Some("localhost") => {
+                req.set_context(&localhost).unwrap();
+                boringssl::ServerNameAction::Accept
+            }
+            _ => boringssl::ServerNameAction::Reject,
+        }
+    });
+    let server_ctx = ssl_ctx.build();
+
+    let ca = boringssl::X509::from_pem(include_bytes!("certs/ca.pem")).unwrap();
+    let mut client_ctx = boringssl::ContextBuilder::new().unwrap();
+    client_ctx.set_verify(boringssl::VerifyMode::Peer);
+    client_ctx.add_trusted_cert(&ca).unwrap();
+    let client_ctx = client_ctx.build();
+
+    // localhost is served with the certificate of the context switched to
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    client.set_hostname("localhost").unwrap();
+    handshake(&mut client, &mut server, |_| false);
+    assert_eq!(client.info().peer_certificate_sha256,
+               Some(cert.sha256_fingerprint().unwrap()));
+
+    // other names are rejected with an unrecognized_name alert
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    client.set_hostname("example.com").unwrap();
+    assert!(matches!(client.handshake(), Err(boringssl::Error::WantRead)));
+    let server_result = server.handshake();
+    assert!(matches!(server_result, Err(boringssl::Error::Ssl(Some(_)))),
+            "{:?}",
+            server_result);
+    match client.handshake() {
+        Err(boringssl::Error::Ssl(Some(alert))) => {
+            assert_eq!(alert,
+                       boringssl::Alert::Received(boringssl::AlertLevel::Fatal,
+                                                  boringssl::AlertDescription::UnrecognizedName));
+        }
+        result => panic!("{:?}", result),
+    }
+}
+
+#[test]
+fn select_certificate_callback() {
+    let cert = boringssl::X509::from_pem(include_bytes!("certs/server.pem")).unwrap();
+    let key = boringssl::PrivateKey::from_pem(include_bytes!("certs/server.key")).unwrap();
+
+    let ca = boringssl::X509::from_pem(include_bytes!("certs/ca.pem")).unwrap();
+    let mut client_ctx = boringssl::ContextBuilder::new().unwrap();
+    client_ctx.set_verify(boringssl::VerifyMode::Peer);
+    client_ctx.add_trusted_cert(&ca).unwrap();
+    let client_ctx = client_ctx.build();
+
+    // the certificate is loaded asynchronously: the first run pauses the
+    // handshake and the retry sets it
+    let calls = Arc::new(AtomicUsize::new(0));
+    let callback_calls = calls.clone();
+    let mut ssl_ctx = boringssl::ContextBuilder::new().unwrap();
+    ssl_ctx.set_select_certificate_callback(move |hello| {
+        if hello.server_name() != Some("localhost") ||
+           !hello.alpn_protocols().contains(&&b"h2"[..]) {
+            return boringssl::SelectCertificateResult::Error;
+        }
+        if callback_calls.fetch_add(1, Ordering::SeqCst) == 0 {
+            return boringssl::SelectCertificateResult::Retry;
+        }
+        hello.set_certificate(&cert).unwrap();
+        hello.set_private_key(&key).unwrap();
+        boringssl::SelectCertificateResult::Success
+    });
+    let server_ctx = ssl_ctx.build();
+
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    client.set_hostname("localhost").unwrap();
+    client.set_alpn_protos(&[b"h2"]).unwrap();
+    let mut pending = 0;
+    handshake(&mut client, &mut server, |err| {
+        match *err {
+            boringssl::Error::PendingCertificate => {
+                pending += 1;
+                true
+            }
+            _ => false,
+        }
+    });
+    assert_eq!(pending, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(client.verify_result().is_ok());
+
+    // the callback switches the connection to a context with the certificate
+    let localhost = server_context().build();
+    let mut ssl_ctx = boringssl::ContextBuilder::new().unwrap();
+    ssl_ctx.set_select_certificate_callback(move |hello| {
+        hello.set_context(&localhost).unwrap();
+        boringssl::SelectCertificateResult::Success
+    });
+    let server_ctx = ssl_ctx.build();
+
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    handshake(&mut client, &mut server, |_| false);
+    assert!(client.verify_result().is_ok());
+}
+
+#[test]
+fn handshake_failure_alert() {
+    // the client offers none of the server's cipher suites
+    let mut server_ctx = server_context();
+    server_ctx.set_max_version(boringssl::TLS1_2_VERSION);
+    server_ctx.set_cipher_list("ECDHE-RSA-AES128-GCM-SHA256");
+    let server_ctx = server_ctx.build();
+    let mut client_ctx = boringssl::ContextBuilder::new().unwrap();
+    client_ctx.set_max_version(boringssl::TLS1_2_VERSION);
+    client_ctx.set_cipher_list("ECDHE-RSA-CHACHA20-POLY1305");
+    let client_ctx = client_ctx.build();
+
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    // the server rejects the ClientHello, then the client reads its alert
+    assert!(matches!(client.handshake(), Err(boringssl::Error::WantRead)));
+    let server_result = server.handshake();
+    let client_result = client.handshake();
+    let handshake_failure = boringssl::AlertDescription::HandshakeFailure;
+    match (client_result, server_result) {
+        (Err(boringssl::Error::Ssl(Some(boringssl::Alert::Received(level, desc)))),
+         Err(boringssl::Error::Ssl(Some(boringssl::Alert::Sent(_, server_desc))))) => {
+            assert_eq!(level, boringssl::AlertLevel::Fatal);
+            assert_eq!(desc, handshake_failure);
+            assert_eq!(server_desc, handshake_failure);
+        }
+        results => panic!("{:?}", results),
+    }
+    assert_eq!(client.last_alert().map(|a| a.description()), Some(handshake_failure));
+}
+
+/// Stands in for a remote signing service: sign only queues the operation and
+/// complete finishes it once the test flips ready.
+#[cfg(boringssl_private_key_method)]
+#[derive(Clone)]
+struct AsyncSigner {
+    key: boringssl::PrivateKey,
+    ready: Arc<AtomicBool>,
+    pending: Option<(u16, Vec<u8>)>,
+}
+
+#[cfg(boringssl_private_key_method)]
+impl boringssl::PrivateKeyMethod for AsyncSigner {
+    fn sign(&mut self, sigalg: u16, input: &[u8], _out: &mut [u8]) -> boringssl::PrivateKeyResult {
+        self.pending = Some((sigalg, input.to_vec()));
+        boringssl::PrivateKeyResult::Retry
+    }
+
+    fn decrypt(&mut self, _input: &[u8], _out: &mut [u8]) -> boringssl::PrivateKeyResult {
+        boringssl::PrivateKeyResult::Failure
+    }
+
+    fn complete(&mut self, out: &mut [u8]) -> boringssl::PrivateKeyResult {
+        if !self.ready.load(Ordering::SeqCst) {
+            return boringssl::PrivateKeyResult::Retry;
+        }
+        let (sigalg, input) = self.pending.take().unwrap();
+        let sig = self.key.sign(sigalg, &input).unwrap();
+        out[..sig.len()].copy_from_slice(&sig);
+        boringssl::PrivateKeyResult::Success(sig.len())
+    }
+}
+
+#[cfg(boringssl_private_key_method)]
+#[test]
+fn async_private_key_method() {
+    let ca = boringssl::X509::from_pem(include_bytes!("certs/ca.pem")).unwrap();
+    let cert = boringssl::X509::from_pem(include_bytes!("certs/server.pem")).unwrap();
+    let key = boringssl::PrivateKey::from_pem(include_bytes!("certs/server.key")).unwrap();
+    let ready = Arc::new(AtomicBool::new(false));
+
+    let mut server_ctx = boringssl::ContextBuilder::new().unwrap();
+    server_ctx.set_certificate(&cert).unwrap();
+    server_ctx.set_private_key_method(AsyncSigner {
+        key: key,
+        ready: ready.clone(),
+        pending: None,
+    });
+    let server_ctx = server_ctx.build();
+
+    let mut client_ctx = boringssl::ContextBuilder::new().unwrap();
+    client_ctx.set_verify(boringssl::VerifyMode::Peer);
+    client_ctx.add_trusted_cert(&ca).unwrap();
+    let client_ctx = client_ctx.build();
+
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    let mut pending = false;
+    handshake(&mut client, &mut server, |err| {
+        match *err {
+            boringssl::Error::WantPrivateKeyOperation => {
+                pending = true;
+                ready.store(true, Ordering::SeqCst);
+                true
+            }
+            _ => false,
+        }
+    });
+    assert!(pending);
+}
+
+#[test]
+fn tls_channel_id() {
+    let channel_id_key =
+        boringssl::PrivateKey::from_pem(include_bytes!("certs/channel_id.key")).unwrap();
+
+    let mut server_ctx = server_context();
+    server_ctx.enable_tls_channel_id();
+    let server_ctx = server_ctx.build();
+
+    // the key is looked up by the callback
+    let mut client_ctx = boringssl::ContextBuilder::new().unwrap();
+    client_ctx.enable_tls_channel_id();
+    let lookup_key = channel_id_key.clone();
+    client_ctx.set_channel_id_callback(move |hostname| {
+        assert_eq!(hostname, Some("localhost"));
+        Some(lookup_key.clone())
+    });
+    let client_ctx = client_ctx.build();
+
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    client.set_hostname("localhost").unwrap();
+    handshake(&mut client, &mut server, |_| false);
+    let id = server.channel_id().unwrap();
+
+    // the key is set on the connection
+    let client_ctx = boringssl::Context::new().unwrap();
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    client.set_channel_id_key(&channel_id_key).unwrap();
+    handshake(&mut client, &mut server, |_| false);
+    assert_eq!(&server.channel_id().unwrap()[..], &id[..]);
+
+    // the callback has no key yet, so the handshake pauses until one is set
+    let mut client_ctx = boringssl::ContextBuilder::new().unwrap();
+    client_ctx.enable_tls_channel_id();
+    client_ctx.set_channel_id_callback(|_| None);
+    let client_ctx = client_ctx.build();
+
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    let mut result = client.handshake();
+    for _ in 0..100 {
+        if !matches!(result, Err(boringssl::Error::WantRead)) {
+            break;
+        }
+        assert!(matches!(server.handshake(), Err(boringssl::Error::WantRead)));
+        result = client.handshake();
+    }
+    assert!(matches!(result, Err(boringssl::Error::WantChannelIdLookup)),
+            "{:?}",
+            result);
+    client.set_channel_id_key(&channel_id_key).unwrap();
+    handshake(&mut client, &mut server, |_| false);
+    assert_eq!(&server.channel_id().unwrap()[..], &id[..]);
+}
+
+#[test]
+fn export_keying_material() {
+    let server_ctx = server_context().build();
+    let client_ctx = boringssl::Context::new().unwrap();
+
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    assert!(client.export_keying_material("EXPERIMENTAL-test", None, 32).is_err());
+    handshake(&mut client, &mut server, |_| false);
+
+    let ekm = client.export_keying_material("EXPERIMENTAL-test", Some(b"ctx"), 32).unwrap();
+    assert_eq!(ekm.len(), 32);
+    assert_eq!(ekm,
+               server.export_keying_material("EXPERIMENTAL-test", Some(b"ctx"), 32).unwrap());
+    assert!(ekm != client.export_keying_material("EXPERIMENTAL-test", Some(b"other"), 32).unwrap());
+    assert_eq!(client.tls_exporter().unwrap(), server.tls_exporter().unwrap());
+    assert_eq!(client.tls_unique(), server.tls_unique());
+}
+
+#[test]
+fn keylog_callback() {
+    let server_ctx = server_context().build();
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let mut client_ctx = boringssl::ContextBuilder::new().unwrap();
+    let keylog = lines.clone();
+    client_ctx.set_keylog_callback(move |line| keylog.lock().unwrap().push(line.to_string()));
+    let client_ctx = client_ctx.build();
+
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    handshake(&mut client, &mut server, |_| false);
+
+    let lines = lines.lock().unwrap();
+    assert!(!lines.is_empty());
+    for line in lines.iter() {
+        assert_eq!(line.split(' ').count(), 3, "{}", line);
+    }
+}
+
+#[test]
+fn handshake_trace() {
+    let server_ctx = server_context().build();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let transcript = Arc::new(Mutex::new(Vec::new()));
+
+    let mut client_ctx = boringssl::ContextBuilder::new().unwrap();
+    let info = events.clone();
+    client_ctx.set_info_callback(move |event| {
+        match *event {
+            boringssl::InfoEvent::HandshakeStart => info.lock().unwrap().push("start"),
+            boringssl::InfoEvent::HandshakeDone => info.lock().unwrap().push("done"),
+            _ => {}
+        }
+    });
+    let msgs = transcript.clone();
+    client_ctx.set_msg_callback(move |msg| {
+        if let Some(t) = msg.handshake_type() {
+            msgs.lock().unwrap().push((msg.direction, t, msg.to_string()));
+        }
+    });
+    let client_ctx = client_ctx.build();
+
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    handshake(&mut client, &mut server, |_| false);
+
+    assert_eq!(*events.lock().unwrap(), vec!["start", "done"]);
+    let transcript = transcript.lock().unwrap();
+    assert_eq!(transcript[0].0, boringssl::Direction::Sent);
+    assert_eq!(transcript[0].1, 1);
+    assert!(transcript[0].2.starts_with(">>> "), "{}", transcript[0].2);
+    assert!(transcript[0].2.contains("ClientHello"), "{}", transcript[0].2);
+    assert!(transcript.iter().any(|m| m.0 == boringssl::Direction::Received && m.1 == 2));
+}
+
+#[test]
+fn connection_info() {
+    let server_ctx = server_context().build();
+    let client_ctx = boringssl::Context::new().unwrap();
+
+    let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+    client.set_hostname("localhost").unwrap();
+    handshake(&mut client, &mut server, |_| false);
+
+    let info = client.info();
+    assert!(info.version >= boringssl::TLS1_2_VERSION);
+    assert!(info.cipher.unwrap().starts_with("TLS_"));
+    assert!(info.group.is_some());
+    assert!(info.signature_algorithm.is_some());
+    assert_eq!(info.alpn_protocol, None);
+    assert!(!info.session_resumed);
+    assert!(!info.early_data_accepted);
+    assert!(!info.false_started);
+    let cert = boringssl::X509::from_pem(include_bytes!("certs/server.pem")).unwrap();
+    assert_eq!(info.peer_certificate_sha256, Some(cert.sha256_fingerprint().unwrap()));
+
+    let server_info = server.info();
+    assert_eq!(server_info.server_name, Some("localhost".to_string()));
+    assert_eq!(server_info.cipher, info.cipher);
+    assert_eq!(server_info.peer_certificate_sha256, None);
+}
+
+#[test]
+fn shared_context() {
+    let server_ctx = server_context().build();
+    let client_ctx = boringssl::Context::new().unwrap();
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let client_ctx = client_ctx.clone();
+            let server_ctx = server_ctx.clone();
+            thread::spawn(move || {
+                let (mut client, mut server, _socks) = connect(&client_ctx, &server_ctx);
+                handshake(&mut client, &mut server, |_| false);
+            })
+        })
+        .collect();
+    drop(server_ctx);
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[test]
+fn connection_moves_between_threads() {
+    let server_ctx = server_context().build();
+    let client_ctx = boringssl::Context::new().unwrap();
+    let (client, server, socks) = connect(&client_ctx, &server_ctx);
+    drop(client_ctx);
+
+    let (mut client, server) = thread::spawn(move || {
+            let mut server = server;
+            let mut client = client;
+            handshake(&mut client, &mut server, |_| false);
+            (client, server)
+        })
+        .join()
+        .unwrap();
+    assert_eq!(client.write(b"hello").unwrap(), 5);
+    drop((server, socks));
+}
+
+#[test]
+fn mem_bio() {
+    let mut bio = boringssl::Bio::new_mem().unwrap();
+    assert_eq!(bio.read(&mut [0; 4]).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+
+    let handle = bio.clone();
+    bio.write_all(b"hello").unwrap();
+    assert_eq!(handle.pending(), 5);
+    assert_eq!(handle.contents(), b"hello");
+    drop(handle);
+
+    let mut buf = [0; 8];
+    assert_eq!(bio.read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], b"hello");
+}
+
+/// Creates a client and a server connected over a BIO pair.
+fn connect_pair(client_ctx: &boringssl::Context,
+                server_ctx: &boringssl::Context)
+                -> (boringssl::Client, boringssl::Server) {
+    let (client_bio, server_bio) = boringssl::Bio::new_pair(0).unwrap();
+    unsafe {
+        (boringssl::Client::new_bio(client_ctx, client_bio).unwrap(),
+         boringssl::Server::new_bio(server_ctx, server_bio).unwrap())
+    }
+}
+
+/// Creates and frees BIOs and connections over BIO pairs repeatedly, so that
+/// leaks show up when the tests are run under AddressSanitizer, as the ASan
+/// job in .travis.yml does, or under valgrind, e.g.
+/// `valgrind --leak-check=full target/debug/test-<hash> bio_`.
+#[test]
+fn bio_leaks() {
+    let server_ctx = server_context().build();
+    let client_ctx = boringssl::Context::new().unwrap();
+
+    for _ in 0..100 {
+        let bio = boringssl::Bio::new_mem().unwrap();
+        drop(bio.clone());
+        let (a, b) = boringssl::Bio::new_pair(1024).unwrap();
+        drop(b);
+        drop(a);
+
+        // the connection outlives the caller's handle of its BIO
+        let (bio, _) = boringssl::Bio::new_pair(0).unwrap();
+        let client = unsafe { boringssl::Client::new_bio(&client_ctx, bio.clone()).unwrap() };
+        drop(bio);
+        drop(client);
+
+        let (mut client, mut server) = connect_pair(&client_ctx, &server_ctx);
+        handshake(&mut client, &mut server, |_| false);
+        assert_eq!(client.write(b"ping").unwrap(), 4);
+        let mut buf = [0; 4];
+        assert_eq!(server.read(&mut buf).unwrap(), 4);
+        assert_eq!(&buf, b"ping");
+    }
+}
+
+#[test]
+fn dtls_over_udp() {
+    let cert = boringssl::X509::from_pem(include_bytes!("certs/server.pem")).unwrap();
+    let key = boringssl::PrivateKey::from_pem(include_bytes!("certs/server.key")).unwrap();
+    let mut server_ctx = boringssl::ContextBuilder::new_dtls().unwrap();
+    server_ctx.set_certificate(&cert).unwrap();
+    server_ctx.set_private_key(&key).unwrap();
+    let server_ctx = server_ctx.build();
+    let mut client_ctx = boringssl::ContextBuilder::new_dtls().unwrap();
+    client_ctx.set_min_version(boringssl::DTLS1_2_VERSION);
+    let client_ctx = client_ctx.build();
+
+    let client_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let server_sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+    client_sock.connect(server_sock.local_addr().unwrap()).unwrap();
+    server_sock.connect(client_sock.local_addr().unwrap()).unwrap();
+    client_sock.set_nonblocking(true).unwrap();
+    server_sock.set_nonblocking(true).unwrap();
+
+    let (mut client, mut server) = unsafe {
+        (boringssl::Client::new_bio(&client_ctx, boringssl::Bio::new_udp(client_sock).unwrap())
+             .unwrap(),
+         boringssl::Server::new_bio(&server_ctx, boringssl::Bio::new_udp(server_sock).unwrap())
+             .unwrap())
+    };
+    client.set_mtu(1200).unwrap();
+
+    // the client arms the retransmission timer after its first flight
+    assert!(client.dtls_timeout().is_none());
+    assert!(matches!(client.handshake(), Err(boringssl::Error::WantRead)));
+    assert!(client.dtls_timeout().unwrap() > Duration::from_millis(0));
+    assert!(!client.handle_dtls_timeout().unwrap());
+
+    handshake(&mut client, &mut server, |_| false);
+    assert_eq!(client.info().version, boringssl::DTLS1_2_VERSION);
+
+    assert_eq!(client.write(b"datagram").unwrap(), 8);
+    let mut buf = [0; 16];
+    let mut n = Err(boringssl::Error::WantRead);
+    for _ in 0..100 {
+        n = server.read(&mut buf);
+        if n.is_ok() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(&buf[..n.unwrap()], b"datagram");
+}
+
+/// A QUIC endpoint's view of its handshake, shared with the test.
+#[cfg(feature = "quic")]
+#[derive(Default)]
+struct QuicState {
+    read_secrets: Vec<(boringssl::EncryptionLevel, Vec<u8>)>,
+    write_secrets: Vec<(boringssl::EncryptionLevel, Vec<u8>)>,
+    outbox: Vec<(boringssl::EncryptionLevel, Vec<u8>)>,
+    alert: Option<boringssl::AlertDescription>,
+}
+
+#[cfg(feature = "quic")]
+struct TestQuicMethod(Arc<Mutex<QuicState>>);
+
+#[cfg(feature = "quic")]
+impl boringssl::QuicMethod for TestQuicMethod {
+    fn set_read_secret(&mut self,
+                       level: boringssl::EncryptionLevel,
+                       _cipher_suite: u16,
+                       secret: &[u8])
+                       -> bool {
+        self.0.lock().unwrap().read_secrets.push((level, secret.to_vec()));
+        true
+    }
+
+    fn set_write_secret(&mut self,
+                        level: boringssl::EncryptionLevel,
+                        _cipher_suite: u16,
+                        secret: &[u8])
+                        -> bool {
+        self.0.lock().unwrap().write_secrets.push((level, secret.to_vec()));
+        true
+    }
+
+    fn add_handshake_data(&mut self, level: boringssl::EncryptionLevel, data: &[u8]) -> bool {
+        self.0.lock().unwrap().outbox.push((level, data.to_vec()));
+        true
+    }
+
+    fn flush_flight(&mut self) -> bool {
+        true
+    }
+
+    fn send_alert(&mut self,
+                  _level: boringssl::EncryptionLevel,
+                  alert: boringssl::AlertDescription)
+                  -> bool {
+        self.0.lock().unwrap().alert = Some(alert);
+        true
+    }
+}
+
+#[cfg(feature = "quic")]
+#[test]
+fn quic_handshake() {
+    let mut server_ctx = server_context();
+    server_ctx.set_min_version(boringssl::TLS1_3_VERSION);
+    server_ctx.set_alpn_protocols(&[b"h3"]).unwrap();
+    let server_ctx = server_ctx.build();
+    let mut client_ctx = boringssl::ContextBuilder::new().unwrap();
+    client_ctx.set_min_version(boringssl::TLS1_3_VERSION);
+    let client_ctx = client_ctx.build();
+
+    let client_state = Arc::new(Mutex::new(QuicState::default()));
+    let server_state = Arc::new(Mutex::new(QuicState::default()));
+    let mut client =
+        boringssl::Client::new_quic(&client_ctx, TestQuicMethod(client_state.clone())).unwrap();
+    let mut server =
+        boringssl::Server::new_quic(&server_ctx, TestQuicMethod(server_state.clone())).unwrap();
+    client.set_alpn_protos(&[b"h3"]).unwrap();
+    client.set_quic_transport_params(b"client params").unwrap();
+    server.set_quic_transport_params(b"server params").unwrap();
+
+    let (mut client_done, mut server_done) = (false, false);
+    for _ in 0..10 {
+        if !client_done {
+            match client.handshake() {
+                Ok(()) => client_done = true,
+                Err(boringssl::Error::WantRead) => {}
+                Err(err) => panic!("client handshake failed: {:?}", err),
+            }
+        }
+        for (level, data) in client_state.lock().unwrap().outbox.drain(..) {
+            server.provide_quic_data(level, &data).unwrap();
+        }
+        if !server_done {
+            match server.handshake() {
+                Ok(()) => server_done = true,
+                Err(boringssl::Error::WantRead) => {}
+                Err(err) => panic!("server handshake failed: {:?}", err),
+            }
+        }
+        for (level, data) in server_state.lock().unwrap().outbox.drain(..) {
+            client.provide_quic_data(level, &data).unwrap();
+        }
+        if client_done && server_done {
+            break;
+        }
+    }
+    assert!(client_done && server_done);
+    client.process_quic_post_handshake().unwrap();
+
+    assert_eq!(client.quic_write_level(), boringssl::EncryptionLevel::Application);
+    assert_eq!(server.quic_read_level(), boringssl::EncryptionLevel::Application);
+    assert_eq!(client.peer_quic_transport_params(), Some(&b"server params"[..]));
+    assert_eq!(server.peer_quic_transport_params(), Some(&b"client params"[..]));
+    assert_eq!(client.info().alpn_protocol, Some(b"h3".to_vec()));
+
+    // each side reads with the secrets the other writes with
+    let client_state = client_state.lock().unwrap();
+    let server_state = server_state.lock().unwrap();
+    assert!(client_state.write_secrets.iter()
+        .any(|s| s.0 == boringssl::EncryptionLevel::Application));
+    assert_eq!(client_state.write_secrets, server_state.read_secrets);
+    assert_eq!(server_state.write_secrets, client_state.read_secrets);
+    assert_eq!(client_state.alert, None);
+}